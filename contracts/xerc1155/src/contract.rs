@@ -0,0 +1,110 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::{
+    entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError,
+    StdResult, SubMsg, WasmMsg,
+};
+use cw2::set_contract_version;
+use router_wasm_bindings::{RouterMsg, RouterQuery, SudoMsg};
+
+use crate::{
+    error::ContractError,
+    execution::{handle_execute, handle_reply, handle_sudo, INSTANTIATE_REPLY_ID},
+    query::handle_query,
+    state::{MINTER, OWNER, SIGNER_PUBLIC_KEY},
+};
+
+use new_crosstalk_sample::common::decode_ed25519_public_key;
+use new_crosstalk_sample::xerc1155::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "xerc1155";
+const CONTRACT_VERSION: &str = "1.0.0";
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response<RouterMsg>, ContractError> {
+    deps.api.debug("Instantiating the contract🚀");
+
+    // Store state with owner address
+    OWNER.save(deps.storage, &info.sender.to_string())?;
+    MINTER.save(
+        deps.storage,
+        &deps.api.addr_validate(&msg.minter)?.to_string(),
+    )?;
+    if let Some(public_key) = msg.public_key {
+        SIGNER_PUBLIC_KEY.save(deps.storage, &decode_ed25519_public_key(&public_key)?)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // the cw1155 contract mints only through this one, its address comes back in the reply
+    let instantiate_cw1155 = WasmMsg::Instantiate {
+        admin: Some(env.contract.address.to_string()),
+        code_id: msg.xerc1155_codeid,
+        msg: to_binary(&cw1155_base::msg::InstantiateMsg {
+            minter: env.contract.address.to_string(),
+        })?,
+        funds: vec![],
+        label: "xerc1155".to_string(),
+    };
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            instantiate_cw1155,
+            INSTANTIATE_REPLY_ID,
+        ))
+        .add_attribute("action", "xcw1155-init"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response<RouterMsg>, ContractError> {
+    handle_execute(deps, env, info, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut<RouterQuery>, env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    let ver: cw2::ContractVersion = cw2::get_contract_version(deps.storage)?;
+    // ensure we are migrating from an allowed contract
+    if ver.contract != CONTRACT_NAME {
+        return Err(StdError::generic_err("Can only upgrade from same type"));
+    }
+    // note: better to do proper semver compare, but string compare *usually* works
+    if ver.version.as_str() >= CONTRACT_VERSION {
+        return Err(StdError::generic_err("Cannot upgrade from a newer version"));
+    }
+
+    let info_str: String = format!(
+        "migrating contract: {}, new_contract_version: {}, contract_name: {}",
+        env.contract.address, CONTRACT_VERSION, CONTRACT_NAME
+    );
+    deps.api.debug(&info_str);
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    handle_query(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(
+    deps: DepsMut<RouterQuery>,
+    env: Env,
+    msg: SudoMsg,
+) -> Result<Response<RouterMsg>, ContractError> {
+    handle_sudo(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response<RouterMsg>, ContractError> {
+    handle_reply(deps, env, msg)
+}
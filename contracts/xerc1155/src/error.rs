@@ -0,0 +1,83 @@
+use cosmwasm_std::StdError;
+use new_crosstalk_sample::common::{InboundRequestError, PublicKeyError};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Auth: Invalid Owner")]
+    InvalidOwner {},
+
+    #[error("Only the minter can mint without a voucher")]
+    Unauthorized {},
+
+    #[error("{0}")]
+    PublicKey(#[from] PublicKeyError),
+
+    #[error("No voucher signer is set")]
+    NoSigner {},
+
+    #[error("Malformed signature: {reason}")]
+    MalformedSignature { reason: String },
+
+    #[error("Signature not match")]
+    SignatureMismatch {},
+
+    #[error("Voucher expired")]
+    VoucherExpired {},
+
+    #[error("Voucher nonce {nonce} already used")]
+    NonceAlreadyUsed { nonce: u64 },
+
+    #[error("The cw1155 contract is not set")]
+    Cw1155NotSet {},
+
+    #[error("Invalid transfer: {reason}")]
+    InvalidTransfer { reason: String },
+
+    #[error("Unknown chain type {chain_type} for chain {chain_id}")]
+    InvalidChainType { chain_id: String, chain_type: u64 },
+
+    #[error("Invalid request metadata: {reason}")]
+    InvalidRequestMetadata { reason: String },
+
+    #[error("No remote contract enrolled for destination chain {chain_id}")]
+    UnknownDestination { chain_id: String },
+
+    #[error("No remote contract enrolled for source chain {chain_id}")]
+    UnknownSource { chain_id: String },
+
+    #[error("Invalid request sender: {sender}")]
+    InvalidRequestSender { sender: String },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Invalid reply: {reason}")]
+    InvalidReply { reason: String },
+
+    #[error("No transfer for request {request_identifier}")]
+    UnknownRequest { request_identifier: u64 },
+
+    #[error("Transfer for request {request_identifier} is already settled")]
+    TransferAlreadySettled { request_identifier: u64 },
+
+    #[error("Cannot decode cross-chain payload: {reason}")]
+    PayloadDecode { reason: String },
+}
+
+impl From<InboundRequestError> for ContractError {
+    fn from(err: InboundRequestError) -> Self {
+        match err {
+            InboundRequestError::Std(err) => ContractError::Std(err),
+            InboundRequestError::UnknownSource { chain_id } => {
+                ContractError::UnknownSource { chain_id }
+            }
+            InboundRequestError::InvalidRequestSender { sender } => {
+                ContractError::InvalidRequestSender { sender }
+            }
+        }
+    }
+}
@@ -0,0 +1,525 @@
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult,
+    Storage, SubMsg, Uint128, WasmMsg,
+};
+use cw1155::Cw1155ExecuteMsg;
+use cw_utils::parse_reply_instantiate_data;
+use new_crosstalk_sample::common::{
+    chain_type_of, check_request_metadata, check_request_sender, crosschain_request_identifier,
+    inbound_ack_response, is_known_chain_type, record_inbound_request,
+};
+use new_crosstalk_sample::xerc1155::{
+    ChainTypeInfo, ExecuteMsg, MintVoucher, SignedVoucher, TransferParams, TransferRecord,
+    TransferStatus,
+};
+use router_wasm_bindings::{
+    ethabi::{decode, encode, Token},
+    types::{ChainType, RequestMetaData},
+    Bytes, RouterMsg, RouterQuery, SudoMsg,
+};
+
+use crate::error::ContractError;
+use crate::state::{
+    CHAIN_TYPES, CW1155_CONTRACT, MINTER, OUTBOUND_IN_FLIGHT, OUTBOUND_TRANSFERS, OWNER,
+    PROCESSED_REQUESTS, REMOTE_CONTRACT_MAPPING, SIGNER_PUBLIC_KEY, USED_NONCES,
+};
+
+pub const INSTANTIATE_REPLY_ID: u64 = 1;
+pub const CREATE_OUTBOUND_REPLY_ID: u64 = 2;
+
+pub fn handle_execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response<RouterMsg>, ContractError> {
+    match msg {
+        ExecuteMsg::EnrollRemoteContract {
+            chain_id,
+            remote_address,
+        } => enroll_remote_contract(deps, env, info, chain_id, remote_address),
+        ExecuteMsg::SetCw1155ContractAddress { address } => {
+            set_cw1155_contract_address(deps, env, info, address)
+        }
+        ExecuteMsg::SetChainTypes { chain_type_info } => {
+            set_chain_types(deps, env, info, chain_type_info)
+        }
+        ExecuteMsg::TransferCrossChain {
+            dst_chain_id,
+            token_ids,
+            token_amounts,
+            token_data,
+            recipient,
+            request_metadata,
+        } => transfer_crosschain(
+            deps,
+            env,
+            info,
+            dst_chain_id,
+            token_ids,
+            token_amounts,
+            token_data,
+            recipient,
+            request_metadata,
+        ),
+        ExecuteMsg::BatchMint {
+            to,
+            batch,
+            msg,
+            voucher,
+        } => batch_mint(deps, env, info, to, batch, msg, voucher),
+        ExecuteMsg::Mint {
+            to,
+            token_id,
+            amount,
+            msg,
+            voucher,
+        } => batch_mint(
+            deps,
+            env,
+            info,
+            to,
+            vec![(token_id.to_string(), amount)],
+            msg,
+            voucher,
+        ),
+    }
+}
+
+pub fn only_owner(deps: Deps, info: MessageInfo) -> Result<(), ContractError> {
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::InvalidOwner {});
+    }
+    Ok(())
+}
+
+pub fn enroll_remote_contract(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    chain_id: String,
+    remote_address: String,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_owner(deps.as_ref(), info)?;
+    REMOTE_CONTRACT_MAPPING.save(deps.storage, chain_id.clone(), &remote_address)?;
+    Ok(Response::new()
+        .add_attribute("action", "enroll_remote_contract")
+        .add_attribute("chain_id", chain_id))
+}
+
+pub fn set_cw1155_contract_address(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_owner(deps.as_ref(), info)?;
+    let address = deps.api.addr_validate(&address)?;
+    CW1155_CONTRACT.save(deps.storage, &address.to_string())?;
+    Ok(Response::new()
+        .add_attribute("action", "set_cw1155_contract_address")
+        .add_attribute("address", address))
+}
+
+pub fn set_chain_types(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    chain_type_info: Vec<ChainTypeInfo>,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_owner(deps.as_ref(), info)?;
+    for chain in chain_type_info {
+        if !is_known_chain_type(chain.chain_type) {
+            return Err(ContractError::InvalidChainType {
+                chain_id: chain.chain_id,
+                chain_type: chain.chain_type,
+            });
+        }
+        CHAIN_TYPES.save(deps.storage, chain.chain_id, &chain.chain_type)?;
+    }
+    Ok(Response::new().add_attribute("action", "set_chain_types"))
+}
+
+fn verify_voucher(
+    deps: DepsMut,
+    env: &Env,
+    to: &str,
+    batch: &[(String, Uint128)],
+    voucher: SignedVoucher,
+) -> Result<(), ContractError> {
+    let public_key = SIGNER_PUBLIC_KEY
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoSigner {})?;
+    let nonce_key = (to.to_string(), voucher.nonce);
+    if USED_NONCES.has(deps.storage, nonce_key.clone()) {
+        return Err(ContractError::NonceAlreadyUsed {
+            nonce: voucher.nonce,
+        });
+    }
+    if voucher.expires.is_expired(&env.block) {
+        return Err(ContractError::VoucherExpired {});
+    }
+
+    let signature =
+        hex::decode(&voucher.signature).map_err(|err| ContractError::MalformedSignature {
+            reason: err.to_string(),
+        })?;
+    let digest = MintVoucher {
+        contract_address: env.contract.address.to_string(),
+        chain_id: env.block.chain_id.clone(),
+        to: to.to_string(),
+        batch: batch.to_vec(),
+        nonce: voucher.nonce,
+        expires: voucher.expires,
+    }
+    .digest();
+    let verified = deps
+        .api
+        .ed25519_verify(&digest, &signature, &public_key)
+        .map_err(|err| ContractError::MalformedSignature {
+            reason: err.to_string(),
+        })?;
+    if !verified {
+        return Err(ContractError::SignatureMismatch {});
+    }
+    USED_NONCES.save(deps.storage, nonce_key, &true)?;
+    Ok(())
+}
+
+/// The minter mints freely, anyone else needs a voucher signed for exactly
+/// this recipient and batch.
+pub fn batch_mint(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: String,
+    batch: Vec<(String, Uint128)>,
+    msg: String,
+    voucher: Option<SignedVoucher>,
+) -> Result<Response<RouterMsg>, ContractError> {
+    let to = deps.api.addr_validate(&to)?.to_string();
+    match voucher {
+        Some(voucher) => verify_voucher(deps.branch(), &env, &to, &batch, voucher)?,
+        None if info.sender == MINTER.load(deps.storage)? => {}
+        None => return Err(ContractError::Unauthorized {}),
+    }
+
+    let msg = if msg.is_empty() {
+        None
+    } else {
+        Some(Binary(msg.into_bytes()))
+    };
+    let token_ids = token_ids_attribute(&batch);
+    Ok(Response::new()
+        .add_message(cw1155_execute(
+            deps.storage,
+            &Cw1155ExecuteMsg::BatchMint {
+                to: to.clone(),
+                batch,
+                msg,
+            },
+        )?)
+        .add_attribute("action", "mint")
+        .add_attribute("to", to)
+        .add_attribute("token_ids", token_ids))
+}
+
+fn cw1155_execute(storage: &dyn Storage, msg: &Cw1155ExecuteMsg) -> Result<WasmMsg, ContractError> {
+    let contract_addr = CW1155_CONTRACT
+        .may_load(storage)?
+        .ok_or(ContractError::Cw1155NotSet {})?;
+    Ok(WasmMsg::Execute {
+        contract_addr,
+        msg: to_binary(msg)?,
+        funds: vec![],
+    })
+}
+
+fn token_ids_attribute(batch: &[(String, Uint128)]) -> String {
+    batch
+        .iter()
+        .map(|(token_id, _)| token_id.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Burns the batch from the sender, who must have approved this contract as
+/// an operator on the cw1155 contract, and sends it to `dst_chain_id`.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_crosschain(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dst_chain_id: String,
+    token_ids: Vec<Uint128>,
+    token_amounts: Vec<Uint128>,
+    token_data: Binary,
+    recipient: String,
+    request_metadata: RequestMetaData,
+) -> Result<Response<RouterMsg>, ContractError> {
+    let remote_address = REMOTE_CONTRACT_MAPPING
+        .may_load(deps.storage, dst_chain_id.clone())?
+        .ok_or_else(|| ContractError::UnknownDestination {
+            chain_id: dst_chain_id.clone(),
+        })?;
+    check_request_metadata(&request_metadata)
+        .map_err(|reason| ContractError::InvalidRequestMetadata { reason })?;
+    if token_ids.is_empty() {
+        return Err(ContractError::InvalidTransfer {
+            reason: "no tokens".to_string(),
+        });
+    }
+    if token_ids.len() != token_amounts.len() {
+        return Err(ContractError::InvalidTransfer {
+            reason: format!(
+                "expected {} amounts, got {}",
+                token_ids.len(),
+                token_amounts.len()
+            ),
+        });
+    }
+
+    let transfer_params = TransferParams {
+        nft_ids: token_ids.clone(),
+        nft_amounts: token_amounts.clone(),
+        nft_data: token_data.0,
+        recipient: recipient.clone(),
+    };
+    // the payload carries the recipient in the destination's address encoding
+    let chain_type = chain_type_of(
+        deps.storage,
+        &CHAIN_TYPES,
+        &dst_chain_id,
+        ChainType::ChainTypeEvm,
+    )?;
+    let encoded_payload: Vec<u8> = encode(&[transfer_params.get_evm_encoding(chain_type)?]);
+    let batch: Vec<(String, Uint128)> = token_ids
+        .iter()
+        .map(|token_id| token_id.to_string())
+        .zip(token_amounts)
+        .collect();
+    let burn = cw1155_execute(
+        deps.storage,
+        &Cw1155ExecuteMsg::BatchBurn {
+            from: info.sender.to_string(),
+            batch: batch.clone(),
+        },
+    )?;
+
+    OUTBOUND_IN_FLIGHT.save(
+        deps.storage,
+        &TransferRecord {
+            // unknown until Router replies, see `save_outbound_transfer`
+            request_identifier: 0,
+            sender: info.sender.to_string(),
+            batch,
+            dst_chain_id: dst_chain_id.clone(),
+            recipient,
+            created_at: env.block.time,
+            status: TransferStatus::Pending,
+        },
+    )?;
+
+    let request_packet: Bytes =
+        encode(&[Token::String(remote_address), Token::Bytes(encoded_payload)]);
+    let i_send_request: RouterMsg = RouterMsg::CrosschainCall {
+        version: 1,
+        route_amount: Uint128::new(0u128),
+        route_recipient: String::from(""),
+        dest_chain_id: dst_chain_id,
+        request_metadata: request_metadata.get_abi_encoded_bytes(),
+        request_packet,
+    };
+
+    Ok(Response::new()
+        .add_message(burn)
+        .add_submessage(SubMsg::reply_on_success(
+            i_send_request,
+            CREATE_OUTBOUND_REPLY_ID,
+        )))
+}
+
+pub fn handle_reply(
+    deps: DepsMut,
+    _env: Env,
+    msg: Reply,
+) -> Result<Response<RouterMsg>, ContractError> {
+    match msg.id {
+        INSTANTIATE_REPLY_ID => {
+            let instantiated =
+                parse_reply_instantiate_data(msg).map_err(|err| ContractError::InvalidReply {
+                    reason: err.to_string(),
+                })?;
+            CW1155_CONTRACT.save(deps.storage, &instantiated.contract_address)?;
+            Ok(Response::new()
+                .add_attribute("action", "cw1155_instantiated")
+                .add_attribute("address", instantiated.contract_address))
+        }
+        CREATE_OUTBOUND_REPLY_ID => save_outbound_transfer(deps, msg),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+/// Files the burned batch under its Router request identifier, where
+/// `GetTransfer` and the ack that settles it look it up.
+fn save_outbound_transfer(deps: DepsMut, msg: Reply) -> Result<Response<RouterMsg>, ContractError> {
+    let request_identifier = crosschain_request_identifier(msg.result)
+        .map_err(|reason| ContractError::InvalidReply { reason })?;
+    let mut transfer = OUTBOUND_IN_FLIGHT.load(deps.storage)?;
+    OUTBOUND_IN_FLIGHT.remove(deps.storage);
+    transfer.request_identifier = request_identifier;
+    OUTBOUND_TRANSFERS.save(deps.storage, transfer.request_identifier, &transfer)?;
+    Ok(Response::new()
+        .add_attribute("action", "transfer_crosschain")
+        .add_attribute(
+            "request_identifier",
+            transfer.request_identifier.to_string(),
+        )
+        .add_attribute("token_ids", token_ids_attribute(&transfer.batch)))
+}
+
+pub fn handle_sudo(
+    deps: DepsMut<RouterQuery>,
+    env: Env,
+    msg: SudoMsg,
+) -> Result<Response<RouterMsg>, ContractError> {
+    match msg {
+        SudoMsg::HandleIReceive {
+            request_sender,
+            src_chain_id,
+            request_identifier,
+            payload,
+        } => handle_sudo_request(
+            deps,
+            env,
+            request_sender,
+            src_chain_id,
+            request_identifier,
+            payload,
+        ),
+        SudoMsg::HandleIAck {
+            request_identifier,
+            exec_flag,
+            exec_data: _,
+            refund_amount: _,
+        } => handle_sudo_ack(deps, env, request_identifier, exec_flag),
+    }
+}
+
+/// Finalizes an outbound transfer, or mints the batch back to its sender when
+/// the destination failed to execute it.
+pub fn handle_sudo_ack(
+    deps: DepsMut<RouterQuery>,
+    _env: Env,
+    request_identifier: u64,
+    exec_flag: bool,
+) -> Result<Response<RouterMsg>, ContractError> {
+    let mut transfer = OUTBOUND_TRANSFERS
+        .may_load(deps.storage, request_identifier)?
+        .ok_or(ContractError::UnknownRequest { request_identifier })?;
+    if transfer.status != TransferStatus::Pending {
+        return Err(ContractError::TransferAlreadySettled { request_identifier });
+    }
+
+    let response = Response::new()
+        .add_attribute("action", "handle_ack")
+        .add_attribute("request_identifier", request_identifier.to_string())
+        .add_attribute("token_ids", token_ids_attribute(&transfer.batch));
+    if exec_flag {
+        transfer.status = TransferStatus::Delivered;
+        OUTBOUND_TRANSFERS.save(deps.storage, request_identifier, &transfer)?;
+        return Ok(response.add_attribute("status", "delivered"));
+    }
+
+    let refund = cw1155_execute(
+        deps.storage,
+        &Cw1155ExecuteMsg::BatchMint {
+            to: transfer.sender.clone(),
+            batch: transfer.batch.clone(),
+            msg: None,
+        },
+    )?;
+    transfer.status = TransferStatus::Refunded;
+    OUTBOUND_TRANSFERS.save(deps.storage, request_identifier, &transfer)?;
+    Ok(response
+        .add_message(refund)
+        .add_attribute("status", "refunded"))
+}
+
+pub fn handle_sudo_request(
+    deps: DepsMut<RouterQuery>,
+    env: Env,
+    request_sender: String,
+    src_chain_id: String,
+    request_identifier: u64,
+    payload: Binary,
+) -> Result<Response<RouterMsg>, ContractError> {
+    check_request_sender(
+        deps.storage,
+        &REMOTE_CONTRACT_MAPPING,
+        &src_chain_id,
+        &request_sender,
+    )?;
+
+    let response = inbound_ack_response(&src_chain_id, request_identifier);
+    // minting is not idempotent, a retried delivery would credit the batch twice
+    if !record_inbound_request(
+        deps.storage,
+        &PROCESSED_REQUESTS,
+        &src_chain_id,
+        request_identifier,
+    )? {
+        return Ok(response.add_attribute("status", "duplicate"));
+    }
+
+    // the payload encodes the recipient for this chain's own type
+    let chain_type = chain_type_of(
+        deps.storage,
+        &CHAIN_TYPES,
+        &env.block.chain_id,
+        ChainType::ChainTypeCosmos,
+    )?;
+    let transfer_params =
+        decode_transfer_payload(&payload, chain_type).map_err(payload_decode_err)?;
+    let recipient = deps.api.addr_validate(&transfer_params.recipient)?;
+    let batch: Vec<(String, Uint128)> = transfer_params
+        .nft_ids
+        .iter()
+        .map(|nft_id| nft_id.to_string())
+        .zip(transfer_params.nft_amounts)
+        .collect();
+    let token_ids = token_ids_attribute(&batch);
+    let msg = if transfer_params.nft_data.is_empty() {
+        None
+    } else {
+        Some(Binary(transfer_params.nft_data))
+    };
+    let mint = cw1155_execute(
+        deps.storage,
+        &Cw1155ExecuteMsg::BatchMint {
+            to: recipient.to_string(),
+            batch,
+            msg,
+        },
+    )?;
+    Ok(response
+        .add_message(mint)
+        .add_attribute("status", "processed")
+        .add_attribute("token_ids", token_ids))
+}
+
+fn decode_transfer_payload(payload: &[u8], chain_type: u64) -> StdResult<TransferParams> {
+    let mut tokens = decode(&[TransferParams::get_params_types()], payload)
+        .map_err(|err| StdError::parse_err("TransferParams", format!("{:?}", err)))?;
+    let tuple = tokens
+        .remove(0)
+        .into_tuple()
+        .ok_or_else(|| StdError::parse_err("TransferParams", "expected tuple"))?;
+    TransferParams::from_token_tuple(tuple, chain_type)
+}
+
+fn payload_decode_err(err: StdError) -> ContractError {
+    ContractError::PayloadDecode {
+        reason: err.to_string(),
+    }
+}
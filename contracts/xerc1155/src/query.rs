@@ -0,0 +1,24 @@
+use cosmwasm_std::{to_binary, Binary, Deps, Env, StdResult};
+use cw2::get_contract_version;
+use new_crosstalk_sample::xerc1155::QueryMsg;
+
+use crate::state::{
+    CHAIN_TYPES, CW1155_CONTRACT, OUTBOUND_TRANSFERS, OWNER, REMOTE_CONTRACT_MAPPING,
+};
+
+pub fn handle_query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetContractVersion {} => to_binary(&get_contract_version(deps.storage)?),
+        QueryMsg::GetOwner {} => to_binary(&OWNER.load(deps.storage)?),
+        QueryMsg::GetRemoteContract { chain_id } => {
+            to_binary(&REMOTE_CONTRACT_MAPPING.load(deps.storage, chain_id)?)
+        }
+        QueryMsg::GetCw1155Address {} => to_binary(&CW1155_CONTRACT.may_load(deps.storage)?),
+        QueryMsg::GetChainType { chain_id } => {
+            to_binary(&CHAIN_TYPES.may_load(deps.storage, chain_id)?)
+        }
+        QueryMsg::GetTransfer { request_identifier } => {
+            to_binary(&OUTBOUND_TRANSFERS.load(deps.storage, request_identifier)?)
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use cosmwasm_std::Binary;
+use cw_storage_plus::{Item, Map};
+use new_crosstalk_sample::xerc1155::TransferRecord;
+
+pub const OWNER: Item<String> = Item::new("owner");
+// the only address that mints without a voucher
+pub const MINTER: Item<String> = Item::new("minter");
+// cw1155 contract holding the balances, this contract is its minter
+pub const CW1155_CONTRACT: Item<String> = Item::new("cw1155_contract");
+// raw ed25519 key signing mint vouchers, missing when only the minter mints
+pub const SIGNER_PUBLIC_KEY: Item<Binary> = Item::new("signer_public_key");
+// (recipient, nonce) => true once the voucher carrying this nonce was used
+pub const USED_NONCES: Map<(String, u64), bool> = Map::new("used_nonces");
+// chain id => xerc1155 contract enrolled there, the only sender accepted from that chain
+pub const REMOTE_CONTRACT_MAPPING: Map<String, String> = Map::new("remote_contract_mapping");
+// chain id => Router `ChainType` code used to encode batch recipients for that chain
+pub const CHAIN_TYPES: Map<String, u64> = Map::new("chain_types");
+// batch burned by the current `TransferCrossChain`, filed by `save_outbound_transfer`
+pub const OUTBOUND_IN_FLIGHT: Item<TransferRecord> = Item::new("outbound_in_flight");
+// Router request identifier => outbound batch, kept after settling for `GetTransfer`
+pub const OUTBOUND_TRANSFERS: Map<u64, TransferRecord> = Map::new("outbound_transfers");
+// (source chain id, Router request identifier) => true once its batch was minted here
+pub const PROCESSED_REQUESTS: Map<(String, u64), bool> = Map::new("processed_requests");
@@ -0,0 +1,737 @@
+use crate::contract::{execute, instantiate, query, reply, sudo};
+use crate::error::ContractError;
+use crate::execution::{CREATE_OUTBOUND_REPLY_ID, INSTANTIATE_REPLY_ID};
+use cw0::Expiration;
+use cw1155::Cw1155ExecuteMsg;
+use ed25519_zebra::{SigningKey, VerificationKey};
+use new_crosstalk_sample::testing::{
+    self, ack_msg, crosschain_call_reply, instantiate_reply, transfer_request_metadata,
+    with_router_deps,
+};
+use new_crosstalk_sample::xerc1155::{
+    ChainTypeInfo, ExecuteMsg, InstantiateMsg, MintVoucher, QueryMsg, SignedVoucher,
+    TransferParams, TransferRecord, TransferStatus,
+};
+use router_wasm_bindings::ethabi::{decode, encode, ParamType, Token};
+use router_wasm_bindings::types::{AckType, ChainType};
+use router_wasm_bindings::{RouterMsg, SudoMsg};
+
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    from_binary, Attribute, Binary, CosmosMsg, DepsMut, Env, OwnedDeps, Response, Uint128, WasmMsg,
+};
+
+const OWNER: &str = "router1sxc6t9uh9u8f252gl3yqetlt6qmp2syx3v0p3w";
+const MINTER: &str = "router1apapk9zfz3rp4x87fsm6h0s3zd0wlmkz0fx8tx";
+const USER: &str = "router1ehk6f4u8dv6zkk9qzw7tpzh2xv6l5fvjh7mmkx";
+const CW1155_ADDRESS: &str = "router1cw1155contract";
+const REMOTE_CONTRACT: &str = "0xc27CE28850774288B3EF678c4550161346944152";
+const EVM_RECIPIENT: &str = "0x1C609537a32630c054202e2B089B9Da268667C5D";
+// test-only ed25519 seed of the voucher signer
+const SIGNER_SEED: [u8; 32] = [7u8; 32];
+
+fn signer_public_key() -> String {
+    let signing_key = SigningKey::from(SIGNER_SEED);
+    hex::encode(VerificationKey::from(&signing_key).as_ref())
+}
+
+fn sign_voucher(env: &Env, to: &str, batch: &[(String, Uint128)], nonce: u64) -> SignedVoucher {
+    sign_voucher_until(env, to, batch, nonce, Expiration::Never {})
+}
+
+fn sign_voucher_until(
+    env: &Env,
+    to: &str,
+    batch: &[(String, Uint128)],
+    nonce: u64,
+    expires: Expiration,
+) -> SignedVoucher {
+    let digest = MintVoucher {
+        contract_address: env.contract.address.to_string(),
+        chain_id: env.block.chain_id.clone(),
+        to: to.to_string(),
+        batch: batch.to_vec(),
+        nonce,
+        expires,
+    }
+    .digest();
+    let signature: [u8; 64] = SigningKey::from(SIGNER_SEED).sign(&digest).into();
+    SignedVoucher {
+        nonce,
+        expires,
+        signature: hex::encode(signature),
+    }
+}
+
+fn do_instantiate(mut deps: DepsMut, public_key: Option<String>) {
+    let msg = InstantiateMsg {
+        minter: MINTER.into(),
+        xerc1155_codeid: 7,
+        public_key,
+    };
+    instantiate(deps.branch(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    reply(
+        deps,
+        mock_env(),
+        instantiate_reply(INSTANTIATE_REPLY_ID, CW1155_ADDRESS),
+    )
+    .unwrap();
+}
+
+fn enroll_remote_contract(deps: DepsMut) {
+    let msg = ExecuteMsg::EnrollRemoteContract {
+        chain_id: "80001".into(),
+        remote_address: REMOTE_CONTRACT.into(),
+    };
+    execute(deps, mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+}
+
+fn do_sudo(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    msg: SudoMsg,
+) -> Result<Response<RouterMsg>, ContractError> {
+    with_router_deps(deps, |deps| sudo(deps, mock_env(), msg))
+}
+
+fn transfer_crosschain_msg(token_ids: Vec<u128>, token_amounts: Vec<u128>) -> ExecuteMsg {
+    ExecuteMsg::TransferCrossChain {
+        dst_chain_id: "80001".into(),
+        token_ids: token_ids.into_iter().map(Uint128::from).collect(),
+        token_amounts: token_amounts.into_iter().map(Uint128::from).collect(),
+        token_data: Binary::default(),
+        recipient: EVM_RECIPIENT.into(),
+        request_metadata: transfer_request_metadata(),
+    }
+}
+
+fn receive_msg(request_sender: &str, request_identifier: u64, recipient: &str) -> SudoMsg {
+    let transfer_params = Token::Tuple(vec![
+        Token::Array(vec![Token::Uint(1.into()), Token::Uint(2.into())]),
+        Token::Array(vec![Token::Uint(10.into()), Token::Uint(20.into())]),
+        Token::Bytes(vec![]),
+        Token::Bytes(recipient.as_bytes().to_vec()),
+    ]);
+    testing::receive_msg(
+        "80001",
+        request_sender,
+        request_identifier,
+        encode(&[transfer_params]),
+    )
+}
+
+fn get_transfer(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, id: u64) -> TransferRecord {
+    let query_msg = QueryMsg::GetTransfer {
+        request_identifier: id,
+    };
+    from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap()
+}
+
+// the cw1155 message a response sends, in order
+fn cw1155_msgs(res: &Response<RouterMsg>) -> Vec<Cw1155ExecuteMsg> {
+    res.messages
+        .iter()
+        .filter_map(|sub_msg| match &sub_msg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                assert_eq!(contract_addr, CW1155_ADDRESS);
+                Some(from_binary(msg).unwrap())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn batch(tokens: &[(u128, u128)]) -> Vec<(String, Uint128)> {
+    tokens
+        .iter()
+        .map(|(token_id, amount)| (token_id.to_string(), Uint128::from(*amount)))
+        .collect()
+}
+
+#[test]
+fn test_instantiate() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        minter: MINTER.into(),
+        xerc1155_codeid: 7,
+        public_key: None,
+    };
+    let res = instantiate(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, INSTANTIATE_REPLY_ID);
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate { code_id, msg, .. }) => {
+            assert_eq!(*code_id, 7);
+            let msg: cw1155_base::msg::InstantiateMsg = from_binary(msg).unwrap();
+            assert_eq!(msg.minter, env.contract.address.to_string());
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+
+    let address: Option<String> =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetCw1155Address {}).unwrap())
+            .unwrap();
+    assert_eq!(address, None);
+    reply(
+        deps.as_mut(),
+        env.clone(),
+        instantiate_reply(INSTANTIATE_REPLY_ID, CW1155_ADDRESS),
+    )
+    .unwrap();
+    let address: Option<String> =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetCw1155Address {}).unwrap())
+            .unwrap();
+    assert_eq!(address, Some(CW1155_ADDRESS.to_string()));
+    let owner: String =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::GetOwner {}).unwrap()).unwrap();
+    assert_eq!(owner, OWNER);
+}
+
+#[test]
+fn test_instantiate_rejects_malformed_public_key() {
+    let mut deps = mock_dependencies();
+    // the last key has the right length but is not a curve point
+    for public_key in [
+        "not hex",
+        "abcd",
+        "0200000000000000000000000000000000000000000000000000000000000000",
+    ] {
+        let msg = InstantiateMsg {
+            minter: MINTER.into(),
+            xerc1155_codeid: 7,
+            public_key: Some(public_key.into()),
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PublicKey(_)));
+    }
+}
+
+#[test]
+fn test_owner_messages() {
+    let mut deps = mock_dependencies();
+    do_instantiate(deps.as_mut(), None);
+
+    let msg = ExecuteMsg::EnrollRemoteContract {
+        chain_id: "80001".into(),
+        remote_address: REMOTE_CONTRACT.into(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info(USER, &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidOwner {});
+    enroll_remote_contract(deps.as_mut());
+    let query_msg = QueryMsg::GetRemoteContract {
+        chain_id: "80001".into(),
+    };
+    let remote: String =
+        from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+    assert_eq!(remote, REMOTE_CONTRACT);
+
+    let msg = ExecuteMsg::SetCw1155ContractAddress {
+        address: "router1othercw1155".into(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info(USER, &[]), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::InvalidOwner {});
+    execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    let address: Option<String> =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetCw1155Address {}).unwrap())
+            .unwrap();
+    assert_eq!(address, Some("router1othercw1155".to_string()));
+}
+
+#[test]
+fn test_minter_mints() {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        minter: MINTER.into(),
+        xerc1155_codeid: 7,
+        public_key: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    let mint_msg = ExecuteMsg::Mint {
+        to: USER.into(),
+        token_id: Uint128::from(1u128),
+        amount: Uint128::from(5u128),
+        msg: "".into(),
+        voucher: None,
+    };
+    // no cw1155 contract to mint on until the instantiate reply
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MINTER, &[]),
+        mint_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Cw1155NotSet {});
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        instantiate_reply(INSTANTIATE_REPLY_ID, CW1155_ADDRESS),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MINTER, &[]),
+        mint_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        cw1155_msgs(&res),
+        vec![Cw1155ExecuteMsg::BatchMint {
+            to: USER.into(),
+            batch: batch(&[(1, 5)]),
+            msg: None,
+        }]
+    );
+
+    let batch_mint_msg = ExecuteMsg::BatchMint {
+        to: USER.into(),
+        batch: batch(&[(1, 5), (2, 1)]),
+        msg: "hello".into(),
+        voucher: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MINTER, &[]),
+        batch_mint_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        cw1155_msgs(&res),
+        vec![Cw1155ExecuteMsg::BatchMint {
+            to: USER.into(),
+            batch: batch(&[(1, 5), (2, 1)]),
+            msg: Some(Binary(b"hello".to_vec())),
+        }]
+    );
+    assert!(res.attributes.contains(&Attribute::new("token_ids", "1,2")));
+
+    for msg in [mint_msg, batch_mint_msg] {
+        let err = execute(deps.as_mut(), mock_env(), mock_info(USER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+}
+
+#[test]
+fn test_voucher_mint() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    do_instantiate(deps.as_mut(), Some(signer_public_key()));
+
+    let tokens = batch(&[(1, 5), (2, 1)]);
+    let voucher = sign_voucher(&env, USER, &tokens, 0);
+    let msg = ExecuteMsg::BatchMint {
+        to: USER.into(),
+        batch: tokens.clone(),
+        msg: "".into(),
+        voucher: Some(voucher.clone()),
+    };
+    // anyone can send the voucher, the tokens go to whom it was signed for
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        cw1155_msgs(&res),
+        vec![Cw1155ExecuteMsg::BatchMint {
+            to: USER.into(),
+            batch: tokens.clone(),
+            msg: None,
+        }]
+    );
+    let err = execute(deps.as_mut(), env.clone(), mock_info(USER, &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::NonceAlreadyUsed { nonce: 0 });
+
+    // the voucher covers exactly the batch and recipient it was signed for
+    let voucher = sign_voucher(&env, USER, &tokens, 1);
+    let msg = ExecuteMsg::BatchMint {
+        to: USER.into(),
+        batch: batch(&[(1, 50), (2, 1)]),
+        msg: "".into(),
+        voucher: Some(voucher.clone()),
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(USER, &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::SignatureMismatch {});
+    let msg = ExecuteMsg::BatchMint {
+        to: MINTER.into(),
+        batch: tokens.clone(),
+        msg: "".into(),
+        voucher: Some(voucher),
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info(USER, &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::SignatureMismatch {});
+
+    let voucher = sign_voucher(&env, USER, &batch(&[(3, 2)]), 1);
+    let msg = ExecuteMsg::Mint {
+        to: USER.into(),
+        token_id: Uint128::from(3u128),
+        amount: Uint128::from(2u128),
+        msg: "".into(),
+        voucher: Some(voucher),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info(USER, &[]), msg).unwrap();
+
+    for signature in ["zz", "abcd"] {
+        let msg = ExecuteMsg::BatchMint {
+            to: USER.into(),
+            batch: tokens.clone(),
+            msg: "".into(),
+            voucher: Some(SignedVoucher {
+                nonce: 2,
+                expires: Expiration::Never {},
+                signature: signature.into(),
+            }),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(USER, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::MalformedSignature { .. }));
+    }
+
+    // the expiry is signed, so it can neither pass nor be pushed back
+    let expires = Expiration::AtHeight(env.block.height);
+    let mint_msg = |voucher: SignedVoucher| ExecuteMsg::BatchMint {
+        to: USER.into(),
+        batch: tokens.clone(),
+        msg: "".into(),
+        voucher: Some(voucher),
+    };
+    let voucher = sign_voucher_until(&env, USER, &tokens, 2, expires);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        mint_msg(voucher.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::VoucherExpired {});
+    let voucher = SignedVoucher {
+        expires: Expiration::AtHeight(env.block.height + 100),
+        ..voucher
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        mint_msg(voucher),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::SignatureMismatch {});
+    let voucher = sign_voucher_until(
+        &env,
+        USER,
+        &tokens,
+        2,
+        Expiration::AtHeight(env.block.height + 100),
+    );
+    execute(deps.as_mut(), env, mock_info(USER, &[]), mint_msg(voucher)).unwrap();
+}
+
+#[test]
+fn test_voucher_needs_a_signer() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    do_instantiate(deps.as_mut(), None);
+
+    let tokens = batch(&[(1, 5)]);
+    let msg = ExecuteMsg::BatchMint {
+        to: USER.into(),
+        batch: tokens.clone(),
+        msg: "".into(),
+        voucher: Some(sign_voucher(&env, USER, &tokens, 0)),
+    };
+    let err = execute(deps.as_mut(), env, mock_info(USER, &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::NoSigner {});
+}
+
+#[test]
+fn test_transfer_crosschain() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    do_instantiate(deps.as_mut(), None);
+
+    let msg = transfer_crosschain_msg(vec![1, 2], vec![10, 20]);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownDestination {
+            chain_id: "80001".into()
+        }
+    );
+    enroll_remote_contract(deps.as_mut());
+
+    for msg in [
+        transfer_crosschain_msg(vec![], vec![]),
+        transfer_crosschain_msg(vec![1, 2], vec![10]),
+    ] {
+        let err = execute(deps.as_mut(), env.clone(), mock_info(USER, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTransfer { .. }));
+    }
+    // without an ack on success the transfer would stay pending
+    let mut ack_on_error = transfer_crosschain_msg(vec![1, 2], vec![10, 20]);
+    if let ExecuteMsg::TransferCrossChain {
+        request_metadata, ..
+    } = &mut ack_on_error
+    {
+        request_metadata.ack_type = AckType::AckOnError;
+    }
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        ack_on_error,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidRequestMetadata {
+            reason: "ack_type must be ack_on_both".into()
+        }
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info(USER, &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        cw1155_msgs(&res),
+        vec![Cw1155ExecuteMsg::BatchBurn {
+            from: USER.into(),
+            batch: batch(&[(1, 10), (2, 20)]),
+        }]
+    );
+    assert_eq!(res.messages[1].id, CREATE_OUTBOUND_REPLY_ID);
+    match &res.messages[1].msg {
+        CosmosMsg::Custom(RouterMsg::CrosschainCall {
+            dest_chain_id,
+            request_packet,
+            ..
+        }) => {
+            assert_eq!(dest_chain_id, "80001");
+            let packet = decode(&[ParamType::String, ParamType::Bytes], request_packet).unwrap();
+            assert_eq!(packet[0], Token::String(REMOTE_CONTRACT.into()));
+            let payload = packet[1].clone().into_bytes().unwrap();
+            let mut tokens = decode(&[TransferParams::get_params_types()], &payload).unwrap();
+            let transfer_params = TransferParams::from_token_tuple(
+                tokens.remove(0).into_tuple().unwrap(),
+                ChainType::ChainTypeEvm.get_chain_code(),
+            )
+            .unwrap();
+            assert_eq!(
+                transfer_params,
+                TransferParams {
+                    nft_ids: vec![1u128.into(), 2u128.into()],
+                    nft_amounts: vec![10u128.into(), 20u128.into()],
+                    nft_data: vec![],
+                    recipient: EVM_RECIPIENT.to_lowercase(),
+                }
+            );
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+
+    reply(
+        deps.as_mut(),
+        env.clone(),
+        crosschain_call_reply(CREATE_OUTBOUND_REPLY_ID, 5),
+    )
+    .unwrap();
+    let transfer = get_transfer(&deps, 5);
+    assert_eq!(transfer.sender, USER);
+    assert_eq!(transfer.batch, batch(&[(1, 10), (2, 20)]));
+    assert_eq!(transfer.status, TransferStatus::Pending);
+
+    let res = do_sudo(&mut deps, ack_msg(5, true)).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("status", "delivered")));
+    assert_eq!(get_transfer(&deps, 5).status, TransferStatus::Delivered);
+    let err = do_sudo(&mut deps, ack_msg(5, false)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TransferAlreadySettled {
+            request_identifier: 5
+        }
+    );
+    let err = do_sudo(&mut deps, ack_msg(6, true)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownRequest {
+            request_identifier: 6
+        }
+    );
+}
+
+#[test]
+fn test_chain_types() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    do_instantiate(deps.as_mut(), None);
+    let near_recipient = "alice.testnet";
+    let set_chain_types_msg = |chain_type: u64| ExecuteMsg::SetChainTypes {
+        chain_type_info: vec![ChainTypeInfo {
+            chain_id: "near-testnet".into(),
+            chain_type,
+        }],
+    };
+    let near = ChainType::ChainTypeNear.get_chain_code();
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        set_chain_types_msg(near),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidOwner {});
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        set_chain_types_msg(9),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidChainType {
+            chain_id: "near-testnet".into(),
+            chain_type: 9
+        }
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        set_chain_types_msg(near),
+    )
+    .unwrap();
+    let query_msg = QueryMsg::GetChainType {
+        chain_id: "near-testnet".into(),
+    };
+    let chain_type: Option<u64> =
+        from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+    assert_eq!(chain_type, Some(near));
+
+    let msg = ExecuteMsg::EnrollRemoteContract {
+        chain_id: "near-testnet".into(),
+        remote_address: "xerc1155.testnet".into(),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap();
+    let msg = ExecuteMsg::TransferCrossChain {
+        dst_chain_id: "near-testnet".into(),
+        token_ids: vec![Uint128::from(1u128)],
+        token_amounts: vec![Uint128::from(10u128)],
+        token_data: Binary::default(),
+        recipient: near_recipient.into(),
+        request_metadata: transfer_request_metadata(),
+    };
+    let res = execute(deps.as_mut(), env, mock_info(USER, &[]), msg).unwrap();
+    match &res.messages[1].msg {
+        CosmosMsg::Custom(RouterMsg::CrosschainCall { request_packet, .. }) => {
+            let packet = decode(&[ParamType::String, ParamType::Bytes], request_packet).unwrap();
+            let payload = packet[1].clone().into_bytes().unwrap();
+            let mut tokens = decode(&[TransferParams::get_params_types()], &payload).unwrap();
+            let params = tokens.remove(0).into_tuple().unwrap();
+            assert_eq!(params[3], Token::Bytes(near_recipient.as_bytes().to_vec()));
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+}
+
+#[test]
+fn test_failed_transfer_is_refunded() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    do_instantiate(deps.as_mut(), None);
+    enroll_remote_contract(deps.as_mut());
+
+    let msg = transfer_crosschain_msg(vec![1, 2], vec![10, 20]);
+    execute(deps.as_mut(), env.clone(), mock_info(USER, &[]), msg).unwrap();
+    reply(
+        deps.as_mut(),
+        env,
+        crosschain_call_reply(CREATE_OUTBOUND_REPLY_ID, 5),
+    )
+    .unwrap();
+
+    let res = do_sudo(&mut deps, ack_msg(5, false)).unwrap();
+    assert_eq!(
+        cw1155_msgs(&res),
+        vec![Cw1155ExecuteMsg::BatchMint {
+            to: USER.into(),
+            batch: batch(&[(1, 10), (2, 20)]),
+            msg: None,
+        }]
+    );
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("status", "refunded")));
+    assert_eq!(get_transfer(&deps, 5).status, TransferStatus::Refunded);
+}
+
+#[test]
+fn test_handle_receive() {
+    let mut deps = mock_dependencies();
+    do_instantiate(deps.as_mut(), None);
+
+    let err = do_sudo(&mut deps, receive_msg(REMOTE_CONTRACT, 1, USER)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownSource {
+            chain_id: "80001".into()
+        }
+    );
+    enroll_remote_contract(deps.as_mut());
+    let err = do_sudo(&mut deps, receive_msg("0xdead", 1, USER)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidRequestSender {
+            sender: "0xdead".into()
+        }
+    );
+
+    let res = do_sudo(&mut deps, receive_msg(REMOTE_CONTRACT, 1, USER)).unwrap();
+    assert_eq!(
+        cw1155_msgs(&res),
+        vec![Cw1155ExecuteMsg::BatchMint {
+            to: USER.into(),
+            batch: batch(&[(1, 10), (2, 20)]),
+            msg: None,
+        }]
+    );
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("status", "processed")));
+    assert_eq!(
+        res.data,
+        Some(Binary(encode(&[Token::String("80001".into())])))
+    );
+
+    // a redelivered request is acked without minting again
+    let res = do_sudo(&mut deps, receive_msg(REMOTE_CONTRACT, 1, USER)).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("status", "duplicate")));
+
+    let malformed = SudoMsg::HandleIReceive {
+        request_sender: REMOTE_CONTRACT.into(),
+        src_chain_id: "80001".into(),
+        request_identifier: 2,
+        payload: Binary(vec![1, 2, 3]),
+    };
+    let err = do_sudo(&mut deps, malformed).unwrap_err();
+    assert!(matches!(err, ContractError::PayloadDecode { .. }));
+}
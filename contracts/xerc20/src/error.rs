@@ -0,0 +1,68 @@
+use cosmwasm_std::StdError;
+use new_crosstalk_sample::common::InboundRequestError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Auth: Invalid Owner")]
+    InvalidOwner {},
+
+    #[error("The cw20 contract is not set")]
+    Xerc20NotSet {},
+
+    #[error("Unknown chain type {chain_type} for chain {chain_id}")]
+    InvalidChainType { chain_id: String, chain_type: u64 },
+
+    #[error("No contracts to whitelist")]
+    NoWhiteListedContracts {},
+
+    #[error("Chain {chain_id} is listed twice")]
+    DuplicateChain { chain_id: String },
+
+    #[error("Invalid contract address for chain {chain_id}: {reason}")]
+    InvalidContractAddress { chain_id: String, reason: String },
+
+    #[error("Invalid transfer: {reason}")]
+    InvalidTransfer { reason: String },
+
+    #[error("Invalid request metadata: {reason}")]
+    InvalidRequestMetadata { reason: String },
+
+    #[error("No contract whitelisted for destination chain {chain_id}")]
+    UnknownDestination { chain_id: String },
+
+    #[error("No contract whitelisted for source chain {chain_id}")]
+    UnknownSource { chain_id: String },
+
+    #[error("Invalid request sender: {sender}")]
+    InvalidRequestSender { sender: String },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Invalid reply: {reason}")]
+    InvalidReply { reason: String },
+
+    #[error("No pending transfer for request {request_identifier}")]
+    UnknownRequest { request_identifier: u64 },
+
+    #[error("Cannot decode cross-chain payload: {reason}")]
+    PayloadDecode { reason: String },
+}
+
+impl From<InboundRequestError> for ContractError {
+    fn from(err: InboundRequestError) -> Self {
+        match err {
+            InboundRequestError::Std(err) => ContractError::Std(err),
+            InboundRequestError::UnknownSource { chain_id } => {
+                ContractError::UnknownSource { chain_id }
+            }
+            InboundRequestError::InvalidRequestSender { sender } => {
+                ContractError::InvalidRequestSender { sender }
+            }
+        }
+    }
+}
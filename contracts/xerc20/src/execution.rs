@@ -0,0 +1,431 @@
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult,
+    Storage, SubMsg, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use cw_utils::parse_reply_instantiate_data;
+use new_crosstalk_sample::common::{
+    chain_type_of, check_request_metadata, check_request_sender, crosschain_request_identifier,
+    inbound_ack_response, is_known_chain_type, record_inbound_request,
+};
+use new_crosstalk_sample::xerc20::{ChainTypeInfo, ContractInfo, ExecuteMsg, TransferParams};
+use router_wasm_bindings::{
+    ethabi::{decode, encode, Token},
+    types::{ChainType, RequestMetaData},
+    utils::{convert_address_from_bytes_to_string, convert_address_from_string_to_bytes},
+    Bytes, RouterMsg, RouterQuery, SudoMsg,
+};
+
+use crate::error::ContractError;
+use crate::state::{
+    PendingTransfer, CHAIN_ID, CHAIN_TYPES, OUTBOUND_IN_FLIGHT, OWNER, PENDING_TRANSFERS,
+    PROCESSED_REQUESTS, WHITELISTED_CONTRACTS, XERC20_CONTRACT,
+};
+
+pub const INSTANTIATE_REPLY_ID: u64 = 1;
+pub const CREATE_OUTBOUND_REPLY_ID: u64 = 2;
+
+pub fn handle_execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response<RouterMsg>, ContractError> {
+    match msg {
+        ExecuteMsg::SetWhiteListedContracts { contracts } => {
+            set_whitelisted_contracts(deps, env, info, contracts)
+        }
+        ExecuteMsg::SetChainId { id } => set_chain_id(deps, env, info, id),
+        ExecuteMsg::SetXerc20Addr { addr } => set_xerc20_addr(deps, env, info, addr),
+        ExecuteMsg::SetChainTypes { chain_type_info } => {
+            set_chain_types(deps, env, info, chain_type_info)
+        }
+        ExecuteMsg::TrasferCrossChain {
+            amount,
+            recipient,
+            dest_chain_id,
+            request_metadata,
+        } => transfer_crosschain(
+            deps,
+            env,
+            info,
+            amount,
+            recipient,
+            dest_chain_id,
+            request_metadata,
+        ),
+    }
+}
+
+pub fn only_owner(deps: Deps, info: MessageInfo) -> Result<(), ContractError> {
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::InvalidOwner {});
+    }
+    Ok(())
+}
+
+pub fn set_whitelisted_contracts(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    contracts: Vec<ContractInfo>,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_owner(deps.as_ref(), info)?;
+    if contracts.is_empty() {
+        return Err(ContractError::NoWhiteListedContracts {});
+    }
+    for (i, contract) in contracts.iter().enumerate() {
+        if contracts[..i]
+            .iter()
+            .any(|other| other.chain_id == contract.chain_id)
+        {
+            return Err(ContractError::DuplicateChain {
+                chain_id: contract.chain_id.clone(),
+            });
+        }
+        check_contract_address(deps.storage, contract)?;
+        WHITELISTED_CONTRACTS.save(
+            deps.storage,
+            contract.chain_id.clone(),
+            &contract.contract_addr,
+        )?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_white_listed_contracts")
+        .add_attribute("count", contracts.len().to_string()))
+}
+
+/// Checks a whitelisted address against its chain's encoding, once the
+/// chain type has been set.
+fn check_contract_address(
+    storage: &dyn Storage,
+    contract: &ContractInfo,
+) -> Result<(), ContractError> {
+    let invalid = |reason: String| ContractError::InvalidContractAddress {
+        chain_id: contract.chain_id.clone(),
+        reason,
+    };
+    if contract.contract_addr.is_empty() {
+        return Err(invalid("address is empty".to_string()));
+    }
+    let chain_type = match CHAIN_TYPES.may_load(storage, contract.chain_id.clone())? {
+        Some(chain_type) => chain_type,
+        None => return Ok(()),
+    };
+    if !is_known_chain_type(chain_type) {
+        return Err(ContractError::InvalidChainType {
+            chain_id: contract.chain_id.clone(),
+            chain_type,
+        });
+    }
+    let address = convert_address_from_string_to_bytes(contract.contract_addr.clone(), chain_type)
+        .map_err(|err| invalid(err.to_string()))?;
+    if chain_type == ChainType::ChainTypeEvm.get_chain_code() && address.len() != 20 {
+        return Err(invalid(format!("expected 20 bytes, got {}", address.len())));
+    }
+    Ok(())
+}
+
+pub fn set_chain_id(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_owner(deps.as_ref(), info)?;
+    CHAIN_ID.save(deps.storage, &id)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_chain_id")
+        .add_attribute("chain_id", id))
+}
+
+pub fn set_xerc20_addr(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    addr: String,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_owner(deps.as_ref(), info)?;
+    let addr = deps.api.addr_validate(&addr)?;
+    XERC20_CONTRACT.save(deps.storage, &addr.to_string())?;
+    Ok(Response::new()
+        .add_attribute("action", "set_xerc20_addr")
+        .add_attribute("address", addr))
+}
+
+pub fn set_chain_types(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    chain_type_info: Vec<ChainTypeInfo>,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_owner(deps.as_ref(), info)?;
+    for info in chain_type_info {
+        if !is_known_chain_type(info.chain_type) {
+            return Err(ContractError::InvalidChainType {
+                chain_id: info.chain_id,
+                chain_type: info.chain_type,
+            });
+        }
+        CHAIN_TYPES.save(deps.storage, info.chain_id, &info.chain_type)?;
+    }
+    Ok(Response::new().add_attribute("action", "set_chain_types"))
+}
+
+fn cw20_execute(storage: &dyn Storage, msg: &Cw20ExecuteMsg) -> Result<WasmMsg, ContractError> {
+    let contract_addr = XERC20_CONTRACT
+        .may_load(storage)?
+        .ok_or(ContractError::Xerc20NotSet {})?;
+    Ok(WasmMsg::Execute {
+        contract_addr,
+        msg: to_binary(msg)?,
+        funds: vec![],
+    })
+}
+
+/// Burns `amount` from the sender, who must have given this contract an
+/// allowance on the cw20 contract, and sends it to `dest_chain_id`.
+pub fn transfer_crosschain(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    recipient: Binary,
+    dest_chain_id: String,
+    request_metadata: RequestMetaData,
+) -> Result<Response<RouterMsg>, ContractError> {
+    let remote_address = WHITELISTED_CONTRACTS
+        .may_load(deps.storage, dest_chain_id.clone())?
+        .ok_or_else(|| ContractError::UnknownDestination {
+            chain_id: dest_chain_id.clone(),
+        })?;
+    check_request_metadata(&request_metadata)
+        .map_err(|reason| ContractError::InvalidRequestMetadata { reason })?;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidTransfer {
+            reason: "amount is zero".to_string(),
+        });
+    }
+    // the recipient is already encoded for the destination, check it when its chain type is known
+    if let Some(chain_type) = CHAIN_TYPES.may_load(deps.storage, dest_chain_id.clone())? {
+        check_recipient(&recipient, chain_type)?;
+    }
+
+    let burn = cw20_execute(
+        deps.storage,
+        &Cw20ExecuteMsg::BurnFrom {
+            owner: info.sender.to_string(),
+            amount,
+        },
+    )?;
+    OUTBOUND_IN_FLIGHT.save(
+        deps.storage,
+        &PendingTransfer {
+            sender: info.sender.to_string(),
+            amount,
+        },
+    )?;
+
+    let transfer_params = TransferParams {
+        recipient: recipient.0,
+        amount,
+    };
+    let request_packet: Bytes = encode(&[
+        Token::String(remote_address),
+        Token::Bytes(encode(&[transfer_params.get_evm_encoding()])),
+    ]);
+    let i_send_request: RouterMsg = RouterMsg::CrosschainCall {
+        version: 1,
+        route_amount: Uint128::new(0u128),
+        route_recipient: String::from(""),
+        dest_chain_id,
+        request_metadata: request_metadata.get_abi_encoded_bytes(),
+        request_packet,
+    };
+
+    Ok(Response::new()
+        .add_message(burn)
+        .add_submessage(SubMsg::reply_on_success(
+            i_send_request,
+            CREATE_OUTBOUND_REPLY_ID,
+        )))
+}
+
+fn check_recipient(recipient: &[u8], chain_type: u64) -> Result<(), ContractError> {
+    if chain_type == ChainType::ChainTypeEvm.get_chain_code() && recipient.len() != 20 {
+        return Err(ContractError::InvalidTransfer {
+            reason: format!("recipient: expected 20 bytes, got {}", recipient.len()),
+        });
+    }
+    convert_address_from_bytes_to_string(recipient, chain_type).map_err(|err| {
+        ContractError::InvalidTransfer {
+            reason: format!("recipient: {}", err),
+        }
+    })?;
+    Ok(())
+}
+
+pub fn handle_reply(
+    deps: DepsMut,
+    _env: Env,
+    msg: Reply,
+) -> Result<Response<RouterMsg>, ContractError> {
+    match msg.id {
+        INSTANTIATE_REPLY_ID => {
+            let instantiated =
+                parse_reply_instantiate_data(msg).map_err(|err| ContractError::InvalidReply {
+                    reason: err.to_string(),
+                })?;
+            XERC20_CONTRACT.save(deps.storage, &instantiated.contract_address)?;
+            Ok(Response::new()
+                .add_attribute("action", "cw20_instantiated")
+                .add_attribute("address", instantiated.contract_address))
+        }
+        CREATE_OUTBOUND_REPLY_ID => save_pending_transfer(deps, msg),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+/// Keeps who burned how much until the ack for `request_identifier` says
+/// whether the amount has to be minted back.
+fn save_pending_transfer(deps: DepsMut, msg: Reply) -> Result<Response<RouterMsg>, ContractError> {
+    let request_identifier = crosschain_request_identifier(msg.result)
+        .map_err(|reason| ContractError::InvalidReply { reason })?;
+    let transfer = OUTBOUND_IN_FLIGHT.load(deps.storage)?;
+    OUTBOUND_IN_FLIGHT.remove(deps.storage);
+    PENDING_TRANSFERS.save(deps.storage, request_identifier, &transfer)?;
+    Ok(Response::new()
+        .add_attribute("action", "transfer_crosschain")
+        .add_attribute("request_identifier", request_identifier.to_string())
+        .add_attribute("amount", transfer.amount))
+}
+
+pub fn handle_sudo(
+    deps: DepsMut<RouterQuery>,
+    env: Env,
+    msg: SudoMsg,
+) -> Result<Response<RouterMsg>, ContractError> {
+    match msg {
+        SudoMsg::HandleIReceive {
+            request_sender,
+            src_chain_id,
+            request_identifier,
+            payload,
+        } => handle_sudo_request(
+            deps,
+            env,
+            request_sender,
+            src_chain_id,
+            request_identifier,
+            payload,
+        ),
+        SudoMsg::HandleIAck {
+            request_identifier,
+            exec_flag,
+            exec_data: _,
+            refund_amount: _,
+        } => handle_sudo_ack(deps, env, request_identifier, exec_flag),
+    }
+}
+
+/// Forgets a delivered transfer, or mints the amount back to its sender when
+/// the destination failed to execute it.
+pub fn handle_sudo_ack(
+    deps: DepsMut<RouterQuery>,
+    _env: Env,
+    request_identifier: u64,
+    exec_flag: bool,
+) -> Result<Response<RouterMsg>, ContractError> {
+    let transfer = PENDING_TRANSFERS
+        .may_load(deps.storage, request_identifier)?
+        .ok_or(ContractError::UnknownRequest { request_identifier })?;
+    PENDING_TRANSFERS.remove(deps.storage, request_identifier);
+
+    let response = Response::new()
+        .add_attribute("action", "handle_ack")
+        .add_attribute("request_identifier", request_identifier.to_string());
+    if exec_flag {
+        return Ok(response.add_attribute("status", "delivered"));
+    }
+    let refund = cw20_execute(
+        deps.storage,
+        &Cw20ExecuteMsg::Mint {
+            recipient: transfer.sender,
+            amount: transfer.amount,
+        },
+    )?;
+    Ok(response
+        .add_message(refund)
+        .add_attribute("status", "refunded"))
+}
+
+pub fn handle_sudo_request(
+    deps: DepsMut<RouterQuery>,
+    env: Env,
+    request_sender: String,
+    src_chain_id: String,
+    request_identifier: u64,
+    payload: Binary,
+) -> Result<Response<RouterMsg>, ContractError> {
+    check_request_sender(
+        deps.storage,
+        &WHITELISTED_CONTRACTS,
+        &src_chain_id,
+        &request_sender,
+    )?;
+
+    let response = inbound_ack_response(&src_chain_id, request_identifier);
+    // the amount is minted fresh on every execution, so a retry must not reach the mint
+    if !record_inbound_request(
+        deps.storage,
+        &PROCESSED_REQUESTS,
+        &src_chain_id,
+        request_identifier,
+    )? {
+        return Ok(response.add_attribute("status", "duplicate"));
+    }
+
+    let transfer_params = decode_transfer_payload(&payload).map_err(payload_decode_err)?;
+    // the recipient's type is looked up under the Router chain id of this deployment
+    let chain_id = CHAIN_ID
+        .may_load(deps.storage)?
+        .unwrap_or(env.block.chain_id);
+    let chain_type = chain_type_of(
+        deps.storage,
+        &CHAIN_TYPES,
+        &chain_id,
+        ChainType::ChainTypeCosmos,
+    )?;
+    let recipient = convert_address_from_bytes_to_string(&transfer_params.recipient, chain_type)
+        .map_err(payload_decode_err)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    let mint = cw20_execute(
+        deps.storage,
+        &Cw20ExecuteMsg::Mint {
+            recipient: recipient.to_string(),
+            amount: transfer_params.amount,
+        },
+    )?;
+    Ok(response
+        .add_message(mint)
+        .add_attribute("status", "processed")
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", transfer_params.amount))
+}
+
+fn decode_transfer_payload(payload: &[u8]) -> StdResult<TransferParams> {
+    let mut tokens = decode(&[TransferParams::get_params_types()], payload)
+        .map_err(|err| StdError::parse_err("TransferParams", format!("{:?}", err)))?;
+    let tuple = tokens
+        .remove(0)
+        .into_tuple()
+        .ok_or_else(|| StdError::parse_err("TransferParams", "expected tuple"))?;
+    TransferParams::from_token_tuple(tuple)
+}
+
+fn payload_decode_err(err: StdError) -> ContractError {
+    ContractError::PayloadDecode {
+        reason: err.to_string(),
+    }
+}
@@ -0,0 +1,11 @@
+#![allow(non_snake_case)]
+
+pub mod contract;
+pub mod error;
+pub mod execution;
+pub mod query;
+pub mod state;
+
+pub use serde::{Deserialize, Serialize};
+#[cfg(test)]
+mod tests;
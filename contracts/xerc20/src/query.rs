@@ -0,0 +1,47 @@
+use cosmwasm_std::{to_binary, Binary, Deps, Env, Order, StdResult};
+use cw2::get_contract_version;
+use cw_storage_plus::Bound;
+use new_crosstalk_sample::xerc20::{ContractInfo, QueryMsg};
+
+use crate::state::{CHAIN_ID, CHAIN_TYPES, OWNER, WHITELISTED_CONTRACTS, XERC20_CONTRACT};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+pub fn handle_query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetContractVersion {} => to_binary(&get_contract_version(deps.storage)?),
+        QueryMsg::FetchOwner {} => to_binary(&OWNER.load(deps.storage)?),
+        QueryMsg::FetchXerc20 {} => to_binary(&XERC20_CONTRACT.may_load(deps.storage)?),
+        QueryMsg::FetchChainId {} => to_binary(&CHAIN_ID.may_load(deps.storage)?),
+        QueryMsg::FetchChainType { chain_id } => {
+            to_binary(&CHAIN_TYPES.may_load(deps.storage, chain_id)?)
+        }
+        QueryMsg::FetchWhiteListedContract { chain_id } => {
+            to_binary(&WHITELISTED_CONTRACTS.load(deps.storage, chain_id)?)
+        }
+        QueryMsg::AllWhiteListedContract { start_after, limit } => {
+            to_binary(&all_whitelisted_contracts(deps, start_after, limit)?)
+        }
+    }
+}
+
+fn all_whitelisted_contracts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<ContractInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    WHITELISTED_CONTRACTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (chain_id, contract_addr) = item?;
+            Ok(ContractInfo {
+                chain_id,
+                contract_addr,
+            })
+        })
+        .collect()
+}
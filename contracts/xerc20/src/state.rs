@@ -0,0 +1,27 @@
+use cosmwasm_std::Uint128;
+use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingTransfer {
+    // burned from `sender`, minted back if the destination fails
+    pub sender: String,
+    pub amount: Uint128,
+}
+
+pub const OWNER: Item<String> = Item::new("owner");
+// cw20 contract holding the balances, this contract is its minter
+pub const XERC20_CONTRACT: Item<String> = Item::new("xerc20_contract");
+// Router chain id of this deployment
+pub const CHAIN_ID: Item<String> = Item::new("chain_id");
+// chain id => Router chain type code
+pub const CHAIN_TYPES: Map<String, u64> = Map::new("chain_types");
+// chain id => token contract trusted on that chain, both as destination and as sender
+pub const WHITELISTED_CONTRACTS: Map<String, String> = Map::new("whitelisted_contracts");
+// amount burned by the current `TrasferCrossChain`, moved to `PENDING_TRANSFERS` by the reply
+pub const OUTBOUND_IN_FLIGHT: Item<PendingTransfer> = Item::new("outbound_in_flight");
+// Router request identifier => transfer waiting for its ack
+pub const PENDING_TRANSFERS: Map<u64, PendingTransfer> = Map::new("pending_transfers");
+// (source chain id, Router request identifier) => true once its amount was minted here
+pub const PROCESSED_REQUESTS: Map<(String, u64), bool> = Map::new("processed_requests");
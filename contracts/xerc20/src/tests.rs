@@ -0,0 +1,554 @@
+use crate::contract::{execute, instantiate, query, reply, sudo};
+use crate::error::ContractError;
+use crate::execution::{CREATE_OUTBOUND_REPLY_ID, INSTANTIATE_REPLY_ID};
+use cw20::Cw20ExecuteMsg;
+use new_crosstalk_sample::testing::{
+    ack_msg, crosschain_call_reply, instantiate_reply, receive_msg, transfer_request_metadata,
+    with_router_deps,
+};
+use new_crosstalk_sample::xerc20::{
+    ChainTypeInfo, ContractInfo, ExecuteMsg, InstantiateMsg, QueryMsg, TransferParams,
+};
+use router_wasm_bindings::ethabi::{decode, encode, ParamType, Token};
+use router_wasm_bindings::types::{AckType, ChainType, RequestMetaData};
+use router_wasm_bindings::{RouterMsg, SudoMsg};
+
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    from_binary, Attribute, Binary, CosmosMsg, DepsMut, OwnedDeps, Response, Uint128, WasmMsg,
+};
+
+const OWNER: &str = "router1sxc6t9uh9u8f252gl3yqetlt6qmp2syx3v0p3w";
+const USER: &str = "router1apapk9zfz3rp4x87fsm6h0s3zd0wlmkz0fx8tx";
+const CW20_ADDRESS: &str = "router1cw20contract";
+const REMOTE_CONTRACT: &str = "0xc27CE28850774288B3EF678c4550161346944152";
+// raw bytes of 0x1C609537a32630c054202e2B089B9Da268667C5D
+const EVM_RECIPIENT: [u8; 20] = [
+    0x1c, 0x60, 0x95, 0x37, 0xa3, 0x26, 0x30, 0xc0, 0x54, 0x20, 0x2e, 0x2b, 0x08, 0x9b, 0x9d, 0xa2,
+    0x68, 0x66, 0x7c, 0x5d,
+];
+
+fn do_instantiate(mut deps: DepsMut) {
+    let msg = InstantiateMsg {
+        cw20_code_id: 7,
+        token_name: "Utility Token".into(),
+        token_symbol: "UTIL".into(),
+        decimals: 6,
+    };
+    instantiate(deps.branch(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    reply(
+        deps,
+        mock_env(),
+        instantiate_reply(INSTANTIATE_REPLY_ID, CW20_ADDRESS),
+    )
+    .unwrap();
+}
+
+fn whitelist_remote_contract(deps: DepsMut) {
+    let msg = ExecuteMsg::SetWhiteListedContracts {
+        contracts: vec![ContractInfo {
+            chain_id: "80001".into(),
+            contract_addr: REMOTE_CONTRACT.into(),
+        }],
+    };
+    execute(deps, mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+}
+
+fn do_sudo(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    msg: SudoMsg,
+) -> Result<Response<RouterMsg>, ContractError> {
+    with_router_deps(deps, |deps| sudo(deps, mock_env(), msg))
+}
+
+fn transfer_crosschain_msg(amount: u128, recipient: &[u8]) -> ExecuteMsg {
+    ExecuteMsg::TrasferCrossChain {
+        amount: Uint128::from(amount),
+        recipient: Binary(recipient.to_vec()),
+        dest_chain_id: "80001".into(),
+        request_metadata: transfer_request_metadata(),
+    }
+}
+
+fn transfer_payload(recipient: &[u8], amount: u128) -> Vec<u8> {
+    let transfer_params = TransferParams {
+        recipient: recipient.to_vec(),
+        amount: amount.into(),
+    };
+    encode(&[transfer_params.get_evm_encoding()])
+}
+
+// the cw20 messages a response sends, in order
+fn cw20_msgs(res: &Response<RouterMsg>) -> Vec<Cw20ExecuteMsg> {
+    res.messages
+        .iter()
+        .filter_map(|sub_msg| match &sub_msg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                assert_eq!(contract_addr, CW20_ADDRESS);
+                Some(from_binary(msg).unwrap())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_instantiate() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        cw20_code_id: 7,
+        token_name: "Utility Token".into(),
+        token_symbol: "UTIL".into(),
+        decimals: 6,
+    };
+    let res = instantiate(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, INSTANTIATE_REPLY_ID);
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate { code_id, msg, .. }) => {
+            assert_eq!(*code_id, 7);
+            let msg: cw20_base::msg::InstantiateMsg = from_binary(msg).unwrap();
+            assert_eq!(msg.name, "Utility Token");
+            assert_eq!(msg.symbol, "UTIL");
+            assert_eq!(msg.decimals, 6);
+            assert!(msg.initial_balances.is_empty());
+            let minter = msg.mint.unwrap();
+            assert_eq!(minter.minter, env.contract.address.to_string());
+            assert_eq!(minter.cap, None);
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+
+    reply(
+        deps.as_mut(),
+        env.clone(),
+        instantiate_reply(INSTANTIATE_REPLY_ID, CW20_ADDRESS),
+    )
+    .unwrap();
+    let address: Option<String> =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FetchXerc20 {}).unwrap()).unwrap();
+    assert_eq!(address, Some(CW20_ADDRESS.to_string()));
+    let owner: String =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::FetchOwner {}).unwrap()).unwrap();
+    assert_eq!(owner, OWNER);
+}
+
+#[test]
+fn test_owner_messages() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    do_instantiate(deps.as_mut());
+
+    let contracts = vec![
+        ContractInfo {
+            chain_id: "80001".into(),
+            contract_addr: REMOTE_CONTRACT.into(),
+        },
+        ContractInfo {
+            chain_id: "43113".into(),
+            contract_addr: "0x00000000000000000000000000000000000000aa".into(),
+        },
+    ];
+    let msgs = [
+        ExecuteMsg::SetWhiteListedContracts {
+            contracts: contracts.clone(),
+        },
+        ExecuteMsg::SetChainId {
+            id: "router_9601-1".into(),
+        },
+        ExecuteMsg::SetXerc20Addr {
+            addr: "router1othercw20".into(),
+        },
+        ExecuteMsg::SetChainTypes {
+            chain_type_info: vec![ChainTypeInfo {
+                chain_id: "80001".into(),
+                chain_type: ChainType::ChainTypeEvm.get_chain_code(),
+            }],
+        },
+    ];
+    for msg in msgs {
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(USER, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidOwner {});
+        execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap();
+    }
+
+    let query_msg = QueryMsg::FetchWhiteListedContract {
+        chain_id: "80001".into(),
+    };
+    let remote: String =
+        from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+    assert_eq!(remote, REMOTE_CONTRACT);
+    let all: Vec<ContractInfo> = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::AllWhiteListedContract {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(all, vec![contracts[1].clone(), contracts[0].clone()]);
+
+    let chain_id: Option<String> =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FetchChainId {}).unwrap())
+            .unwrap();
+    assert_eq!(chain_id, Some("router_9601-1".to_string()));
+    let address: Option<String> =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FetchXerc20 {}).unwrap()).unwrap();
+    assert_eq!(address, Some("router1othercw20".to_string()));
+    let query_msg = QueryMsg::FetchChainType {
+        chain_id: "80001".into(),
+    };
+    let chain_type: Option<u64> =
+        from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+    assert_eq!(chain_type, Some(ChainType::ChainTypeEvm.get_chain_code()));
+
+    let msg = ExecuteMsg::SetChainTypes {
+        chain_type_info: vec![ChainTypeInfo {
+            chain_id: "80001".into(),
+            chain_type: 42,
+        }],
+    };
+    let err = execute(deps.as_mut(), env, mock_info(OWNER, &[]), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidChainType {
+            chain_id: "80001".into(),
+            chain_type: 42
+        }
+    );
+}
+
+#[test]
+fn test_transfer_crosschain() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    do_instantiate(deps.as_mut());
+
+    let msg = transfer_crosschain_msg(100, &EVM_RECIPIENT);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownDestination {
+            chain_id: "80001".into()
+        }
+    );
+    whitelist_remote_contract(deps.as_mut());
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        transfer_crosschain_msg(0, &EVM_RECIPIENT),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidTransfer { .. }));
+    for (ack_type, is_read_call, reason) in [
+        (AckType::AckOnError, false, "ack_type must be ack_on_both"),
+        (
+            AckType::AckOnBoth,
+            true,
+            "a transfer can not be a read call",
+        ),
+    ] {
+        let msg = ExecuteMsg::TrasferCrossChain {
+            amount: Uint128::from(100u128),
+            recipient: Binary(EVM_RECIPIENT.to_vec()),
+            dest_chain_id: "80001".into(),
+            request_metadata: RequestMetaData {
+                ack_type,
+                is_read_call,
+                ..transfer_request_metadata()
+            },
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(USER, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidRequestMetadata {
+                reason: reason.into()
+            }
+        );
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info(USER, &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        cw20_msgs(&res),
+        vec![Cw20ExecuteMsg::BurnFrom {
+            owner: USER.into(),
+            amount: Uint128::from(100u128),
+        }]
+    );
+    assert_eq!(res.messages[1].id, CREATE_OUTBOUND_REPLY_ID);
+    match &res.messages[1].msg {
+        CosmosMsg::Custom(RouterMsg::CrosschainCall {
+            dest_chain_id,
+            request_packet,
+            ..
+        }) => {
+            assert_eq!(dest_chain_id, "80001");
+            let packet = decode(&[ParamType::String, ParamType::Bytes], request_packet).unwrap();
+            assert_eq!(packet[0], Token::String(REMOTE_CONTRACT.into()));
+            assert_eq!(
+                packet[1],
+                Token::Bytes(transfer_payload(&EVM_RECIPIENT, 100))
+            );
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+
+    reply(
+        deps.as_mut(),
+        env,
+        crosschain_call_reply(CREATE_OUTBOUND_REPLY_ID, 5),
+    )
+    .unwrap();
+    let res = do_sudo(&mut deps, ack_msg(5, true)).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("status", "delivered")));
+    let err = do_sudo(&mut deps, ack_msg(5, true)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownRequest {
+            request_identifier: 5
+        }
+    );
+}
+
+#[test]
+fn test_recipient_is_checked_against_the_chain_type() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    do_instantiate(deps.as_mut());
+    whitelist_remote_contract(deps.as_mut());
+    let msg = ExecuteMsg::SetChainTypes {
+        chain_type_info: vec![ChainTypeInfo {
+            chain_id: "80001".into(),
+            chain_type: ChainType::ChainTypeEvm.get_chain_code(),
+        }],
+    };
+    execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap();
+
+    let msg = transfer_crosschain_msg(100, &[1, 2, 3]);
+    let err = execute(deps.as_mut(), env.clone(), mock_info(USER, &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidTransfer { .. }));
+    let msg = transfer_crosschain_msg(100, &EVM_RECIPIENT);
+    execute(deps.as_mut(), env, mock_info(USER, &[]), msg).unwrap();
+}
+
+#[test]
+fn test_failed_transfer_is_refunded() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    do_instantiate(deps.as_mut());
+    whitelist_remote_contract(deps.as_mut());
+
+    let msg = transfer_crosschain_msg(100, &EVM_RECIPIENT);
+    execute(deps.as_mut(), env.clone(), mock_info(USER, &[]), msg).unwrap();
+    reply(
+        deps.as_mut(),
+        env,
+        crosschain_call_reply(CREATE_OUTBOUND_REPLY_ID, 5),
+    )
+    .unwrap();
+
+    let res = do_sudo(&mut deps, ack_msg(5, false)).unwrap();
+    assert_eq!(
+        cw20_msgs(&res),
+        vec![Cw20ExecuteMsg::Mint {
+            recipient: USER.into(),
+            amount: Uint128::from(100u128),
+        }]
+    );
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("status", "refunded")));
+}
+
+#[test]
+fn test_handle_receive() {
+    let mut deps = mock_dependencies();
+    do_instantiate(deps.as_mut());
+
+    let payload = transfer_payload(USER.as_bytes(), 250);
+    let err = do_sudo(
+        &mut deps,
+        receive_msg("80001", REMOTE_CONTRACT, 1, payload.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownSource {
+            chain_id: "80001".into()
+        }
+    );
+    whitelist_remote_contract(deps.as_mut());
+    let err = do_sudo(
+        &mut deps,
+        receive_msg("80001", "0xdead", 1, payload.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidRequestSender {
+            sender: "0xdead".into()
+        }
+    );
+
+    let res = do_sudo(
+        &mut deps,
+        receive_msg("80001", REMOTE_CONTRACT, 1, payload.clone()),
+    )
+    .unwrap();
+    assert_eq!(
+        cw20_msgs(&res),
+        vec![Cw20ExecuteMsg::Mint {
+            recipient: USER.into(),
+            amount: Uint128::from(250u128),
+        }]
+    );
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("status", "processed")));
+    assert_eq!(
+        res.data,
+        Some(Binary(encode(&[Token::String("80001".into())])))
+    );
+
+    // Router retrying the same request gets its ack, the recipient is not credited twice
+    let res = do_sudo(&mut deps, receive_msg("80001", REMOTE_CONTRACT, 1, payload)).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("status", "duplicate")));
+
+    let err = do_sudo(
+        &mut deps,
+        receive_msg("80001", REMOTE_CONTRACT, 2, vec![1, 2, 3]),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::PayloadDecode { .. }));
+}
+
+#[test]
+fn test_whitelist_validation() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    do_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::SetChainTypes {
+        chain_type_info: vec![ChainTypeInfo {
+            chain_id: "80001".into(),
+            chain_type: ChainType::ChainTypeEvm.get_chain_code(),
+        }],
+    };
+    execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap();
+
+    let whitelist = |contracts: Vec<(&str, &str)>| ExecuteMsg::SetWhiteListedContracts {
+        contracts: contracts
+            .into_iter()
+            .map(|(chain_id, contract_addr)| ContractInfo {
+                chain_id: chain_id.into(),
+                contract_addr: contract_addr.into(),
+            })
+            .collect(),
+    };
+    let cases = [
+        (vec![], ContractError::NoWhiteListedContracts {}),
+        (
+            vec![("43113", REMOTE_CONTRACT), ("43113", REMOTE_CONTRACT)],
+            ContractError::DuplicateChain {
+                chain_id: "43113".into(),
+            },
+        ),
+        (
+            vec![("43113", "")],
+            ContractError::InvalidContractAddress {
+                chain_id: "43113".into(),
+                reason: "address is empty".into(),
+            },
+        ),
+        (
+            vec![("80001", "0xc27CE28850774288B3EF678c45501613469441")],
+            ContractError::InvalidContractAddress {
+                chain_id: "80001".into(),
+                reason: "expected 20 bytes, got 19".into(),
+            },
+        ),
+    ];
+    for (contracts, expected) in cases {
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            whitelist(contracts),
+        )
+        .unwrap_err();
+        assert_eq!(err, expected);
+    }
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        whitelist(vec![("80001", "router1notevm")]),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidContractAddress { .. }));
+
+    // a chain without a type yet only needs a non-empty address
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(OWNER, &[]),
+        whitelist(vec![
+            ("80001", REMOTE_CONTRACT),
+            ("osmosis-1", "osmo1remote"),
+        ]),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_whitelisted_contracts_are_paginated() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    do_instantiate(deps.as_mut());
+    let contracts: Vec<ContractInfo> = (0..35)
+        .map(|i| ContractInfo {
+            chain_id: format!("chain-{:02}", i),
+            contract_addr: REMOTE_CONTRACT.into(),
+        })
+        .collect();
+    let msg = ExecuteMsg::SetWhiteListedContracts {
+        contracts: contracts.clone(),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap();
+
+    let page = |start_after: Option<String>, limit: Option<u32>| -> Vec<ContractInfo> {
+        let msg = QueryMsg::AllWhiteListedContract { start_after, limit };
+        from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap()
+    };
+    assert_eq!(page(None, None), contracts[..10]);
+    assert_eq!(page(None, Some(100)), contracts[..30]);
+    assert_eq!(page(Some("chain-29".into()), Some(100)), contracts[30..]);
+}
@@ -1,38 +1,86 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{
-    entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response, StdError,
+    StdResult, Storage,
 };
 use cw2::set_contract_version;
+use cw721::ContractInfoResponse;
 // use cw721::Cw721Query::ContractInfoResponse;
 use router_wasm_bindings::{RouterMsg, RouterQuery, SudoMsg};
 
 use crate::{
-    execution::{handle_execute, handle_sudo, Cw721ExecuteMsg, Cw721QueryMsg},
+    error::ContractError,
+    execution::{
+        handle_execute, handle_reply, handle_sudo, Cw721ExecuteMsg, Cw721NFTContract, Cw721QueryMsg,
+    },
     query::handle_query,
-    state::{OWNER, TOTAL_SUPPLY, PUBLIC_KEY},
+    signature::decode_public_key,
+    state::{
+        SignerKey, BRIDGE_MODE, LEGACY_ALREADY_MINTED, LEGACY_PUBLIC_KEY, MINT_COUNT, MINT_POLICY,
+        OWNER, SIGNERS, SIGNER_THRESHOLD, TOKEN_ID_NAMESPACE, TOTAL_SUPPLY,
+    },
 };
 
-use new_crosstalk_sample::xerc721::{InstantiateMsg, MigrateMsg};
+use new_crosstalk_sample::xerc721::{
+    InstantiateMsg, MigrateMsg, MintPolicy, SignatureScheme, DEFAULT_SIGNER_KEY_ID,
+    MAX_TOKEN_ID_NAMESPACE,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "xerc721";
-const CONTRACT_VERSION: &str = "1.0.0";
+const CONTRACT_VERSION: &str = "1.1.0";
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
-    _env: Env,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     deps.api.debug("Instantiating the contract🚀");
 
     // Store state with owner address
     OWNER.save(deps.storage, &info.sender.to_string())?;
 
+    // cw721-base keeps the collection info and minter, and sets its own
+    // contract version, which is overwritten below
+    Cw721NFTContract::default().instantiate(
+        deps.branch(),
+        env.clone(),
+        info.clone(),
+        cw721_base::InstantiateMsg {
+            name: msg.name,
+            symbol: msg.symbol,
+            minter: msg.minter.unwrap_or_else(|| info.sender.to_string()),
+        },
+    )?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     TOTAL_SUPPLY.save(deps.storage, &0)?;
-    PUBLIC_KEY.save(deps.storage, &msg.public_key)?;
+    if msg.token_id_namespace > MAX_TOKEN_ID_NAMESPACE {
+        return Err(ContractError::InvalidTokenIdNamespace {
+            namespace: msg.token_id_namespace,
+            max: MAX_TOKEN_ID_NAMESPACE,
+        });
+    }
+    if msg.mint_policy.per_address_limit() == Some(0) {
+        return Err(ContractError::InvalidMintPolicy {});
+    }
+    TOKEN_ID_NAMESPACE.save(deps.storage, &msg.token_id_namespace)?;
+    BRIDGE_MODE.save(deps.storage, &msg.bridge_mode)?;
+    SIGNERS.save(
+        deps.storage,
+        DEFAULT_SIGNER_KEY_ID.to_string(),
+        &SignerKey {
+            public_key: decode_public_key(&SignatureScheme::Ed25519, &msg.public_key)?,
+            scheme: SignatureScheme::Ed25519,
+            added_at: env.block.time,
+            added_at_height: env.block.height,
+            revoked_at: None,
+        },
+    )?;
+    SIGNER_THRESHOLD.save(deps.storage, &1)?;
+    MINT_POLICY.save(deps.storage, &msg.mint_policy)?;
 
     Ok(Response::new().add_attribute("action", "xcw721-init"))
 }
@@ -43,39 +91,117 @@ pub fn execute(
     env: Env,
     info: MessageInfo,
     msg: Cw721ExecuteMsg,
-) -> StdResult<Response<RouterMsg>> {
+) -> Result<Response<RouterMsg>, ContractError> {
     handle_execute(deps, env, info, msg)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut<RouterQuery>, env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(
+    mut deps: DepsMut<RouterQuery>,
+    env: Env,
+    msg: MigrateMsg,
+) -> Result<Response, ContractError> {
     let ver: cw2::ContractVersion = cw2::get_contract_version(deps.storage)?;
     // ensure we are migrating from an allowed contract
-    if ver.contract != CONTRACT_NAME.to_string() {
+    if ver.contract != CONTRACT_NAME {
         return Err(StdError::generic_err("Can only upgrade from same type").into());
     }
     // note: better to do proper semver compare, but string compare *usually* works
-    if ver.version >= CONTRACT_VERSION.to_string() {
+    if ver.version.as_str() >= CONTRACT_VERSION {
         return Err(StdError::generic_err("Cannot upgrade from a newer version").into());
     }
 
     let info_str: String = format!(
         "migrating contract: {}, new_contract_version: {}, contract_name: {}",
-        env.contract.address,
-        CONTRACT_VERSION.to_string(),
-        CONTRACT_NAME.to_string()
+        env.contract.address, CONTRACT_VERSION, CONTRACT_NAME
     );
     deps.api.debug(&info_str);
+    migrate_collection_info(deps.branch(), msg)?;
+    migrate_legacy_state(deps.storage, &env)?;
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     Ok(Response::default())
 }
 
+/// Saves the cw721 collection info and minter that 1.0.0 deployments lack,
+/// leaving a deployment that has them untouched.
+fn migrate_collection_info(
+    deps: DepsMut<RouterQuery>,
+    msg: MigrateMsg,
+) -> Result<(), ContractError> {
+    let tract = Cw721NFTContract::default();
+    if tract.contract_info.may_load(deps.storage)?.is_none() {
+        let (name, symbol) = match (msg.name, msg.symbol) {
+            (Some(name), Some(symbol)) => (name, symbol),
+            _ => return Err(ContractError::MissingCollectionInfo {}),
+        };
+        tract
+            .contract_info
+            .save(deps.storage, &ContractInfoResponse { name, symbol })?;
+    }
+    if tract.minter.may_load(deps.storage)?.is_none() {
+        let minter = match msg.minter {
+            Some(minter) => minter,
+            None => OWNER.load(deps.storage)?,
+        };
+        let minter = deps.api.addr_validate(&minter)?;
+        tract.minter.save(deps.storage, &minter)?;
+    }
+    Ok(())
+}
+
+/// Brings a 1.0.0 deployment to the current layout: its single public key
+/// becomes the `default` signer, and addresses that already minted keep a
+/// count of one, so that the one-per-address policy still holds for them.
+fn migrate_legacy_state(storage: &mut dyn Storage, env: &Env) -> Result<(), ContractError> {
+    if MINT_POLICY.may_load(storage)?.is_none() {
+        MINT_POLICY.save(storage, &MintPolicy::OnePerAddress {})?;
+    }
+    if SIGNER_THRESHOLD.may_load(storage)?.is_none() {
+        SIGNER_THRESHOLD.save(storage, &1)?;
+    }
+
+    if let Some(public_key) = LEGACY_PUBLIC_KEY.may_load(storage)? {
+        SIGNERS.save(
+            storage,
+            DEFAULT_SIGNER_KEY_ID.to_string(),
+            &SignerKey {
+                public_key: decode_public_key(&SignatureScheme::Ed25519, &public_key)?,
+                scheme: SignatureScheme::Ed25519,
+                added_at: env.block.time,
+                added_at_height: env.block.height,
+                revoked_at: None,
+            },
+        )?;
+        LEGACY_PUBLIC_KEY.remove(storage);
+    }
+
+    let minted = LEGACY_ALREADY_MINTED
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (minter, already_minted) in minted {
+        if already_minted && !MINT_COUNT.has(storage, minter.clone()) {
+            MINT_COUNT.save(storage, minter.clone(), &1)?;
+        }
+        LEGACY_ALREADY_MINTED.remove(storage, minter);
+    }
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: Cw721QueryMsg) -> StdResult<Binary> {
     handle_query(deps, env, msg)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn sudo(deps: DepsMut<RouterQuery>, env: Env, msg: SudoMsg) -> StdResult<Response<RouterMsg>> {
+pub fn sudo(
+    deps: DepsMut<RouterQuery>,
+    env: Env,
+    msg: SudoMsg,
+) -> Result<Response<RouterMsg>, ContractError> {
     handle_sudo(deps, env, msg)
 }
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response<RouterMsg>, ContractError> {
+    handle_reply(deps, env, msg)
+}
@@ -0,0 +1,171 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+use new_crosstalk_sample::common::InboundRequestError;
+use new_crosstalk_sample::xerc721::Role;
+
+use crate::signature::SignatureError;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Cw721(#[from] cw721_base::ContractError),
+
+    #[error("{0}")]
+    Signature(#[from] SignatureError),
+
+    #[error("Auth: Invalid Owner")]
+    InvalidOwner {},
+
+    #[error("Missing role: {role:?}")]
+    MissingRole { role: Role },
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("No ownership transfer in progress")]
+    NoPendingOwner {},
+
+    #[error("Sender is not the pending owner")]
+    NotPendingOwner {},
+
+    #[error("Ownership proposal expired")]
+    OwnershipProposalExpired {},
+
+    #[error("Mint limit reached")]
+    MintLimitReached {},
+
+    #[error("Voucher expired")]
+    VoucherExpired {},
+
+    #[error("Migrating a 1.0.0 deployment needs the collection name and symbol")]
+    MissingCollectionInfo {},
+
+    #[error("A per-address mint limit must be at least 1")]
+    InvalidMintPolicy {},
+
+    #[error("Voucher already consumed")]
+    VoucherAlreadyConsumed {},
+
+    #[error("Voucher nonce {nonce} already used")]
+    NonceAlreadyUsed { nonce: u64 },
+
+    #[error("Unknown or revoked signer: {key_id}")]
+    UnknownSigner { key_id: String },
+
+    #[error("Duplicate signer: {key_id}")]
+    DuplicateSigner { key_id: String },
+
+    #[error("Not enough signatures: {got} of {required} required")]
+    NotEnoughSignatures { got: u32, required: u32 },
+
+    #[error("Signer already exists: {key_id}")]
+    SignerAlreadyExists { key_id: String },
+
+    #[error("Signer already revoked: {key_id}")]
+    SignerAlreadyRevoked { key_id: String },
+
+    #[error("Threshold {threshold} must be between 1 and the number of active signers ({active})")]
+    InvalidThreshold { threshold: u32, active: u32 },
+
+    #[error("Only {active} active signers would be left for a threshold of {threshold}")]
+    SignersBelowThreshold { active: u32, threshold: u32 },
+
+    #[error("Token id namespace {namespace} is larger than {max}")]
+    InvalidTokenIdNamespace { namespace: u32, max: u32 },
+
+    #[error("Token id namespace is exhausted")]
+    TokenIdsExhausted {},
+
+    #[error("Token id {token_id} is not a number")]
+    InvalidTokenId { token_id: String },
+
+    #[error("Token id {token_id} is outside namespace {namespace}")]
+    ForeignTokenId { token_id: u64, namespace: u32 },
+
+    #[error("Token {token_id} is not escrowed by this home deployment")]
+    NotEscrowed { token_id: u64 },
+
+    #[error("Token {token_id} already exists")]
+    TokenIdCollision { token_id: u64 },
+
+    #[error("A batch holds 1 to {max} tokens, got {size}")]
+    InvalidBatchSize { size: usize, max: usize },
+
+    #[error("Token {token_id} is listed twice")]
+    DuplicateToken { token_id: u64 },
+
+    #[error("No remote contract enrolled for chain {chain_id}")]
+    ChainNotEnrolled { chain_id: String },
+
+    #[error("No remote contracts to enroll")]
+    NoRemoteContracts {},
+
+    #[error("Chain {chain_id} may not send admin commands")]
+    NotAdminSource { chain_id: String },
+
+    #[error("Chain {chain_id} is listed twice")]
+    DuplicateChain { chain_id: String },
+
+    #[error("No remote contract enrolled for destination chain {chain_id}")]
+    UnknownDestination { chain_id: String },
+
+    #[error("No remote contract enrolled for source chain {chain_id}")]
+    UnknownSource { chain_id: String },
+
+    #[error("Chain {chain_id} is disabled")]
+    ChainDisabled { chain_id: String },
+
+    #[error("Unknown chain type {chain_type} for chain {chain_id}")]
+    InvalidChainType { chain_id: String, chain_type: u64 },
+
+    #[error("Invalid request metadata: {reason}")]
+    InvalidRequestMetadata { reason: String },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Invalid CrosschainCall reply: {reason}")]
+    InvalidReply { reason: String },
+
+    #[error("No transfer for request {request_identifier}")]
+    UnknownRequest { request_identifier: u64 },
+
+    #[error("Transfer for request {request_identifier} is already settled")]
+    TransferAlreadySettled { request_identifier: u64 },
+
+    #[error("Invalid request sender: {sender}")]
+    InvalidRequestSender { sender: String },
+
+    #[error("Unsupported payload version {version}")]
+    UnsupportedPayloadVersion { version: u8 },
+
+    #[error("Token {token_id} did not come from chain {chain_id}")]
+    NotTokenOrigin { token_id: u64, chain_id: String },
+
+    #[error("Remote deployments can pause but not unpause")]
+    RemoteUnpause {},
+
+    #[error("Unknown message type {message_type}")]
+    UnknownMessageType { message_type: u8 },
+
+    #[error("Cannot decode cross-chain payload: {reason}")]
+    PayloadDecode { reason: String },
+}
+
+impl From<InboundRequestError> for ContractError {
+    fn from(err: InboundRequestError) -> Self {
+        match err {
+            InboundRequestError::Std(err) => ContractError::Std(err),
+            InboundRequestError::UnknownSource { chain_id } => {
+                ContractError::UnknownSource { chain_id }
+            }
+            InboundRequestError::InvalidRequestSender { sender } => {
+                ContractError::InvalidRequestSender { sender }
+            }
+        }
+    }
+}
@@ -1,17 +1,41 @@
 use cosmwasm_std::{
-    Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult, Uint128,
+    Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Order, Reply, Response, StdError,
+    StdResult, Storage, SubMsg, Uint128,
+};
+use cw0::Expiration;
+use cw721_base::{state::TokenInfo, Cw721Contract, MintMsg};
+use new_crosstalk_sample::common::{
+    chain_type_of, check_request_metadata, check_request_sender, crosschain_request_identifier,
+    inbound_ack_response, is_known_chain_type, record_inbound_request,
+};
+use new_crosstalk_sample::xerc721::{
+    namespaced_token_id, split_envelope, AdminCommand, BatchTransferParams, BridgeMode,
+    ChainTypeInfo, CrossChainMessage, ExecuteMsg, MetadataUpdateParams, MintVoucher, QueryMsg,
+    RemoteContract, Role, SignatureScheme, TransferParams, TransferRecord, TransferStatus,
+    TransferredToken, VoucherSignature, LEGACY_PAYLOAD_VERSION, MAX_BATCH_SIZE, MESSAGE_TYPE_ADMIN,
+    MESSAGE_TYPE_BATCH_TRANSFER, MESSAGE_TYPE_METADATA_UPDATE, MESSAGE_TYPE_TRANSFER,
+    PAYLOAD_VERSION, TOKEN_ID_SEQUENCE_BITS,
 };
-use cw721_base::{state::TokenInfo, ContractError, Cw721Contract};
-use new_crosstalk_sample::xerc721::{ExecuteMsg, QueryMsg, TransferParams};
-use ed25519_zebra::{Signature, VerificationKey};
 // use rand::Rng;
 use router_wasm_bindings::{
     ethabi::{decode, encode, ParamType, Token},
-    types::RequestMetaData,
+    types::{ChainType, RequestMetaData},
+    utils::convert_address_from_string_to_bytes,
     Bytes, RouterMsg, RouterQuery, SudoMsg,
 };
 
-use crate::state::{OWNER, REMOTE_CONTRACT_MAPPING, ALREADY_MINTED, TOTAL_SUPPLY, PUBLIC_KEY};
+use crate::error::ContractError;
+use crate::query::is_paused;
+use crate::signature::{decode_public_key, signature_hash, verify_signature};
+use crate::state::{
+    PendingOwner, SignerKey, ADMIN_SOURCES, BRIDGE_MODE, CHAIN_TYPES, CONSUMED_VOUCHERS,
+    DISABLED_CHAINS, ESCROWED, MINT_COUNT, MINT_POLICY, OUTBOUND_IN_FLIGHT, OUTBOUND_TRANSFERS,
+    OWNER, PAUSED, PAYLOAD_VERSIONS, PENDING_OWNER, PROCESSED_REQUESTS, REMOTE_CONTRACT_MAPPING,
+    ROLES, SIGNERS, SIGNER_THRESHOLD, TOKEN_ID_NAMESPACE, TOKEN_ORIGIN, TOTAL_SUPPLY,
+    TRANSFERS_BY_SENDER, TRANSFERS_BY_TOKEN, USED_NONCES,
+};
+pub const CREATE_OUTBOUND_REPLY_ID: u64 = 1;
+
 pub type Cw721NFTContract<'a> = Cw721Contract<'a, Empty, Empty, ExecuteMsg, QueryMsg>;
 pub type Cw721ExecuteMsg = cw721_base::ExecuteMsg<Empty, ExecuteMsg>;
 pub type Cw721QueryMsg = cw721_base::QueryMsg<QueryMsg>;
@@ -21,13 +45,34 @@ pub fn handle_execute(
     env: Env,
     info: MessageInfo,
     msg: Cw721ExecuteMsg,
-) -> StdResult<Response<RouterMsg>> {
+) -> Result<Response<RouterMsg>, ContractError> {
     match msg {
         Cw721ExecuteMsg::Extension { msg } => match msg {
             ExecuteMsg::EnrollRemoteContract {
                 chain_id,
                 remote_address,
             } => enroll_remote_contract(deps, env, info, chain_id, remote_address),
+            ExecuteMsg::EnrollRemoteContracts { contracts } => {
+                enroll_remote_contracts(deps, env, info, contracts)
+            }
+            ExecuteMsg::UnenrollRemoteContract { chain_id } => {
+                unenroll_remote_contract(deps, env, info, chain_id)
+            }
+            ExecuteMsg::DisableChain { chain_id } => {
+                set_chain_enabled(deps, env, info, chain_id, false)
+            }
+            ExecuteMsg::EnableChain { chain_id } => {
+                set_chain_enabled(deps, env, info, chain_id, true)
+            }
+            ExecuteMsg::SetChainTypes { chain_type_info } => {
+                set_chain_types(deps, env, info, chain_type_info)
+            }
+            ExecuteMsg::SetPayloadVersion { chain_id, version } => {
+                set_payload_version(deps, env, info, chain_id, version)
+            }
+            ExecuteMsg::SetAdminSource { chain_id, allowed } => {
+                set_admin_source(deps, env, info, chain_id, allowed)
+            }
             ExecuteMsg::TransferCrossChain {
                 dst_chain_id,
                 token_id,
@@ -42,34 +87,249 @@ pub fn handle_execute(
                 recipient,
                 request_metadata,
             ),
-            ExecuteMsg::MintToken { token_uri, signature } => {
-                mint_token(deps, env, info, token_uri, signature)
+            ExecuteMsg::TransferCrossChainBatch {
+                dst_chain_id,
+                token_ids,
+                recipient,
+                request_metadata,
+            } => transfer_crosschain_batch(
+                deps,
+                env,
+                info,
+                dst_chain_id,
+                token_ids,
+                recipient,
+                request_metadata,
+            ),
+            ExecuteMsg::MintToken {
+                token_uri,
+                nonce,
+                expires,
+                signatures,
+            } => mint_token(deps, env, info, token_uri, nonce, expires, signatures),
+            ExecuteMsg::AddSigner {
+                key_id,
+                public_key,
+                scheme,
+            } => add_signer(deps, env, info, key_id, public_key, scheme),
+            ExecuteMsg::RevokeSigner { key_id } => revoke_signer(deps, env, info, key_id),
+            ExecuteMsg::RotateSigner {
+                key_id,
+                new_key_id,
+                public_key,
+                scheme,
+            } => rotate_signer(deps, env, info, key_id, new_key_id, public_key, scheme),
+            ExecuteMsg::SetSignerThreshold { threshold } => {
+                set_signer_threshold(deps, env, info, threshold)
             }
-        },
-        _ => match Cw721NFTContract::default().execute(deps, env, info, msg) {
-            Ok(cw721_res) => {
-                let response: Response<RouterMsg> = Response::<RouterMsg>::new()
-                    .add_attributes(cw721_res.attributes)
-                    .add_events(cw721_res.events);
-                return Ok(response);
+            ExecuteMsg::UpdateCollectionInfo { name, symbol } => {
+                update_collection_info(deps, env, info, name, symbol)
             }
-            Err(err) => {
-                return Err(StdError::GenericErr {
-                    msg: err.to_string(),
-                })
+            ExecuteMsg::UpdateMinter { minter } => update_minter(deps, env, info, minter),
+            ExecuteMsg::ProposeNewOwner { new_owner, expires } => {
+                propose_new_owner(deps, env, info, new_owner, expires)
             }
+            ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+            ExecuteMsg::CancelOwnershipTransfer {} => cancel_ownership_transfer(deps, env, info),
+            ExecuteMsg::RenounceOwnership {} => renounce_ownership(deps, env, info),
+            ExecuteMsg::GrantRole { role, address } => grant_role(deps, env, info, role, address),
+            ExecuteMsg::RevokeRole { role, address } => revoke_role(deps, env, info, role, address),
+            ExecuteMsg::Pause {} => set_paused(deps, env, info, true),
+            ExecuteMsg::Unpause {} => set_paused(deps, env, info, false),
         },
+        Cw721ExecuteMsg::Mint(mint_msg) => admin_mint(deps, env, info, mint_msg),
+        _ => {
+            let cw721_res = Cw721NFTContract::default().execute(deps, env, info, msg)?;
+            let response: Response<RouterMsg> = Response::<RouterMsg>::new()
+                .add_attributes(cw721_res.attributes)
+                .add_events(cw721_res.events);
+            Ok(response)
+        }
     }
 }
 
-pub fn only_owner(deps: Deps, info: MessageInfo) -> StdResult<Response<RouterMsg>> {
-    if info.sender.to_string() != OWNER.load(deps.storage).unwrap() {
-        return Err(StdError::GenericErr {
-            msg: "Auth: Invalid Owner".into(),
-        });
+pub fn only_owner(deps: Deps, info: MessageInfo) -> Result<(), ContractError> {
+    match OWNER.may_load(deps.storage)? {
+        Some(owner) if info.sender == owner => Ok(()),
+        _ => Err(ContractError::InvalidOwner {}),
+    }
+}
+
+fn has_role(deps: Deps, address: &str, role: Role) -> StdResult<bool> {
+    if OWNER.may_load(deps.storage)?.as_deref() == Some(address) {
+        return Ok(true);
+    }
+    let roles = ROLES
+        .may_load(deps.storage, address.to_string())?
+        .unwrap_or_default();
+    Ok(roles.contains(&role) || roles.contains(&Role::Admin))
+}
+
+/// Passes for the owner, admins and holders of `role`.
+pub fn only_role(deps: Deps, info: MessageInfo, role: Role) -> Result<(), ContractError> {
+    if has_role(deps, info.sender.as_str(), role)? {
+        Ok(())
     } else {
-        Ok(Response::new())
+        Err(ContractError::MissingRole { role })
+    }
+}
+
+fn ensure_not_paused(deps: Deps) -> Result<(), ContractError> {
+    if is_paused(deps)? {
+        return Err(ContractError::Paused {});
     }
+    Ok(())
+}
+
+pub fn grant_role(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::Admin)?;
+    let address = deps.api.addr_validate(&address)?.to_string();
+    let mut roles = ROLES
+        .may_load(deps.storage, address.clone())?
+        .unwrap_or_default();
+    if !roles.contains(&role) {
+        roles.push(role);
+        ROLES.save(deps.storage, address.clone(), &roles)?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "grant_role")
+        .add_attribute("role", format!("{:?}", role))
+        .add_attribute("address", address))
+}
+
+pub fn revoke_role(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::Admin)?;
+    let mut roles = ROLES
+        .may_load(deps.storage, address.clone())?
+        .unwrap_or_default();
+    roles.retain(|granted| *granted != role);
+    if roles.is_empty() {
+        ROLES.remove(deps.storage, address.clone());
+    } else {
+        ROLES.save(deps.storage, address.clone(), &roles)?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "revoke_role")
+        .add_attribute("role", format!("{:?}", role))
+        .add_attribute("address", address))
+}
+
+pub fn set_paused(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::Pauser)?;
+    PAUSED.save(deps.storage, &paused)?;
+    Ok(Response::new().add_attribute("action", if paused { "pause" } else { "unpause" }))
+}
+
+/// cw721 `mint`, open to the cw721 minter and to holders of the minter role.
+pub fn admin_mint(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    mint_msg: MintMsg<Empty>,
+) -> Result<Response<RouterMsg>, ContractError> {
+    ensure_not_paused(deps.as_ref())?;
+    let tract = Cw721NFTContract::default();
+    let minter = tract.minter.load(deps.storage)?;
+    if info.sender != minter {
+        only_role(deps.as_ref(), info.clone(), Role::Minter)?;
+    }
+    let minter_info = MessageInfo {
+        sender: minter,
+        funds: info.funds,
+    };
+    check_local_token_id(deps.storage, &mint_msg.token_id)?;
+    let cw721_res = tract.mint(deps, env, minter_info, mint_msg)?;
+    Ok(Response::new()
+        .add_attributes(cw721_res.attributes)
+        .add_attribute("sender", info.sender))
+}
+
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_owner: String,
+    expires: Expiration,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_owner(deps.as_ref(), info)?;
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::OwnershipProposalExpired {});
+    }
+    PENDING_OWNER.save(
+        deps.storage,
+        &PendingOwner {
+            owner: new_owner.to_string(),
+            expires,
+        },
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "propose_new_owner")
+        .add_attribute("pending_owner", new_owner)
+        .add_attribute("expires", expires.to_string()))
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<RouterMsg>, ContractError> {
+    let pending = match PENDING_OWNER.may_load(deps.storage)? {
+        Some(pending) => pending,
+        None => return Err(ContractError::NoPendingOwner {}),
+    };
+    if info.sender != pending.owner {
+        return Err(ContractError::NotPendingOwner {});
+    }
+    if pending.expires.is_expired(&env.block) {
+        return Err(ContractError::OwnershipProposalExpired {});
+    }
+    OWNER.save(deps.storage, &pending.owner)?;
+    PENDING_OWNER.remove(deps.storage);
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("owner", pending.owner))
+}
+
+pub fn cancel_ownership_transfer(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_owner(deps.as_ref(), info)?;
+    if PENDING_OWNER.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingOwner {});
+    }
+    PENDING_OWNER.remove(deps.storage);
+    Ok(Response::new().add_attribute("action", "cancel_ownership_transfer"))
+}
+
+pub fn renounce_ownership(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_owner(deps.as_ref(), info)?;
+    OWNER.remove(deps.storage);
+    PENDING_OWNER.remove(deps.storage);
+    Ok(Response::new().add_attribute("action", "renounce_ownership"))
 }
 
 pub fn enroll_remote_contract(
@@ -78,48 +338,363 @@ pub fn enroll_remote_contract(
     info: MessageInfo,
     chain_id: String,
     remote_address: String,
-) -> StdResult<Response<RouterMsg>> {
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::BridgeManager)?;
+    REMOTE_CONTRACT_MAPPING.save(deps.storage, chain_id.clone(), &remote_address)?;
+    Ok(Response::new()
+        .add_attribute("action", "enroll_remote_contract")
+        .add_attribute("chain_id", chain_id))
+}
+
+pub fn enroll_remote_contracts(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    contracts: Vec<RemoteContract>,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::BridgeManager)?;
+    if contracts.is_empty() {
+        return Err(ContractError::NoRemoteContracts {});
+    }
+    let mut res = Response::new().add_attribute("action", "enroll_remote_contracts");
+    for (i, contract) in contracts.iter().enumerate() {
+        if contracts[..i]
+            .iter()
+            .any(|other| other.chain_id == contract.chain_id)
+        {
+            return Err(ContractError::DuplicateChain {
+                chain_id: contract.chain_id.clone(),
+            });
+        }
+        REMOTE_CONTRACT_MAPPING.save(
+            deps.storage,
+            contract.chain_id.clone(),
+            &contract.remote_address,
+        )?;
+        res = res.add_attribute("chain_id", contract.chain_id.clone());
+    }
+    Ok(res)
+}
+
+pub fn unenroll_remote_contract(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    chain_id: String,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::BridgeManager)?;
+    ensure_enrolled(deps.as_ref(), &chain_id)?;
+    REMOTE_CONTRACT_MAPPING.remove(deps.storage, chain_id.clone());
+    // a later enroll of the chain starts from a clean slate
+    DISABLED_CHAINS.remove(deps.storage, chain_id.clone());
+    CHAIN_TYPES.remove(deps.storage, chain_id.clone());
+    PAYLOAD_VERSIONS.remove(deps.storage, chain_id.clone());
+    ADMIN_SOURCES.remove(deps.storage, chain_id.clone());
+    Ok(Response::new()
+        .add_attribute("action", "unenroll_remote_contract")
+        .add_attribute("chain_id", chain_id))
+}
+
+pub fn set_chain_enabled(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    chain_id: String,
+    enabled: bool,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::BridgeManager)?;
+    ensure_enrolled(deps.as_ref(), &chain_id)?;
+    if enabled {
+        DISABLED_CHAINS.remove(deps.storage, chain_id.clone());
+    } else {
+        DISABLED_CHAINS.save(deps.storage, chain_id.clone(), &true)?;
+    }
+    Ok(Response::new()
+        .add_attribute(
+            "action",
+            if enabled {
+                "enable_chain"
+            } else {
+                "disable_chain"
+            },
+        )
+        .add_attribute("chain_id", chain_id))
+}
+
+pub fn set_payload_version(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    chain_id: String,
+    version: u8,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::BridgeManager)?;
+    ensure_enrolled(deps.as_ref(), &chain_id)?;
+    match version {
+        LEGACY_PAYLOAD_VERSION => PAYLOAD_VERSIONS.remove(deps.storage, chain_id.clone()),
+        PAYLOAD_VERSION => PAYLOAD_VERSIONS.save(deps.storage, chain_id.clone(), &version)?,
+        _ => return Err(ContractError::UnsupportedPayloadVersion { version }),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_payload_version")
+        .add_attribute("chain_id", chain_id)
+        .add_attribute("version", version.to_string()))
+}
+
+pub fn set_admin_source(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    chain_id: String,
+    allowed: bool,
+) -> Result<Response<RouterMsg>, ContractError> {
     only_owner(deps.as_ref(), info)?;
-    REMOTE_CONTRACT_MAPPING.save(deps.storage, chain_id, &remote_address)?;
-    Ok(Response::new())
+    if allowed {
+        ensure_enrolled(deps.as_ref(), &chain_id)?;
+        ADMIN_SOURCES.save(deps.storage, chain_id.clone(), &true)?;
+    } else {
+        ADMIN_SOURCES.remove(deps.storage, chain_id.clone());
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_admin_source")
+        .add_attribute("chain_id", chain_id)
+        .add_attribute("allowed", allowed.to_string()))
 }
 
-pub fn transfer_crosschain(
+/// The payload version `chain_id` expects, routes never set are legacy.
+pub fn payload_version_of(storage: &dyn Storage, chain_id: &str) -> StdResult<u8> {
+    Ok(PAYLOAD_VERSIONS
+        .may_load(storage, chain_id.to_string())?
+        .unwrap_or(LEGACY_PAYLOAD_VERSION))
+}
+
+fn ensure_enrolled(deps: Deps, chain_id: &str) -> Result<(), ContractError> {
+    if !REMOTE_CONTRACT_MAPPING.has(deps.storage, chain_id.to_string()) {
+        return Err(ContractError::ChainNotEnrolled {
+            chain_id: chain_id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn ensure_chain_enabled(storage: &dyn Storage, chain_id: &str) -> Result<(), ContractError> {
+    if DISABLED_CHAINS.has(storage, chain_id.to_string()) {
+        return Err(ContractError::ChainDisabled {
+            chain_id: chain_id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+pub fn set_chain_types(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    dst_chain_id: String,
-    token_id: u64,
-    recipient: String,
-    request_metadata: RequestMetaData,
-) -> StdResult<Response<RouterMsg>> {
+    chain_type_info: Vec<ChainTypeInfo>,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::BridgeManager)?;
+    for chain in chain_type_info {
+        if !is_known_chain_type(chain.chain_type) {
+            return Err(ContractError::InvalidChainType {
+                chain_id: chain.chain_id,
+                chain_type: chain.chain_type,
+            });
+        }
+        CHAIN_TYPES.save(deps.storage, chain.chain_id, &chain.chain_type)?;
+    }
+    Ok(Response::new().add_attribute("action", "set_chain_types"))
+}
+
+/// Where an outbound transfer goes, resolved by `check_transfer`.
+pub struct Route {
+    pub remote_address: String,
+    pub chain_type: u64,
+    pub payload_version: u8,
+}
+
+/// Everything a cross-chain transfer checks before any token is taken, also
+/// answered by the `CheckTransfer` query.
+pub fn check_transfer(
+    deps: Deps,
+    env: &Env,
+    info: &MessageInfo,
+    dst_chain_id: &str,
+    token_ids: &[u64],
+    recipient: &str,
+    request_metadata: &RequestMetaData,
+) -> Result<Route, ContractError> {
+    ensure_not_paused(deps)?;
+    let remote_address = REMOTE_CONTRACT_MAPPING
+        .may_load(deps.storage, dst_chain_id.to_string())?
+        .ok_or_else(|| ContractError::UnknownDestination {
+            chain_id: dst_chain_id.to_string(),
+        })?;
+    ensure_chain_enabled(deps.storage, dst_chain_id)?;
+    let chain_type = chain_type_of(
+        deps.storage,
+        &CHAIN_TYPES,
+        dst_chain_id,
+        ChainType::ChainTypeEvm,
+    )?;
+    convert_address_from_string_to_bytes(recipient.to_string(), chain_type)?;
+    check_request_metadata(request_metadata)
+        .map_err(|reason| ContractError::InvalidRequestMetadata { reason })?;
+
+    if token_ids.is_empty() || token_ids.len() > MAX_BATCH_SIZE {
+        return Err(ContractError::InvalidBatchSize {
+            size: token_ids.len(),
+            max: MAX_BATCH_SIZE,
+        });
+    }
     let tract = Cw721NFTContract::default();
-    let rider_info = tract.tokens.load(deps.storage, &token_id.to_string())?;
-    match tract.check_can_send(deps.as_ref(), &_env, &info, &rider_info) {
-        Err(_) => {
-            return Err(StdError::GenericErr {
-                msg: "ContractError::Unauthorized".to_string(),
-            })
+    for (i, token_id) in token_ids.iter().enumerate() {
+        if token_ids[..i].contains(token_id) {
+            return Err(ContractError::DuplicateToken {
+                token_id: *token_id,
+            });
         }
-        _ => (),
+        let rider_info = tract.tokens.load(deps.storage, &token_id.to_string())?;
+        tract.check_can_send(deps, env, info, &rider_info)?;
     }
 
-    // burn nft
-    tract.tokens.remove(deps.storage, &token_id.to_string())?;
-    tract.decrement_tokens(deps.storage)?;
+    Ok(Route {
+        remote_address,
+        chain_type,
+        payload_version: payload_version_of(deps.storage, dst_chain_id)?,
+    })
+}
 
-    let dst_contract_add: String = REMOTE_CONTRACT_MAPPING
-        .load(deps.storage, dst_chain_id.clone())
-        .unwrap();
+pub fn transfer_crosschain(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dst_chain_id: String,
+    token_id: u64,
+    recipient: String,
+    request_metadata: RequestMetaData,
+) -> Result<Response<RouterMsg>, ContractError> {
+    let route = check_transfer(
+        deps.as_ref(),
+        &env,
+        &info,
+        &dst_chain_id,
+        &[token_id],
+        &recipient,
+        &request_metadata,
+    )?;
+    let tokens = take_tokens(deps.branch(), &env, &[token_id])?;
     let transfer_params = TransferParams {
         nft_id: token_id,
+        recipient: recipient.clone(),
+        uri: tokens[0].token_uri.clone().unwrap_or_default(),
+    };
+    let encoded_payload: Vec<u8> = CrossChainMessage::Transfer(transfer_params)
+        .encode_payload(route.chain_type, route.payload_version)?;
+    send_crosschain(
+        deps,
+        &env,
+        &info,
+        dst_chain_id,
+        route,
         recipient,
-        uri: rider_info.token_uri.clone().unwrap_or_default(),
+        tokens,
+        encoded_payload,
+        request_metadata,
+    )
+}
+
+pub fn transfer_crosschain_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dst_chain_id: String,
+    token_ids: Vec<u64>,
+    recipient: String,
+    request_metadata: RequestMetaData,
+) -> Result<Response<RouterMsg>, ContractError> {
+    let route = check_transfer(
+        deps.as_ref(),
+        &env,
+        &info,
+        &dst_chain_id,
+        &token_ids,
+        &recipient,
+        &request_metadata,
+    )?;
+    let tokens = take_tokens(deps.branch(), &env, &token_ids)?;
+    let transfer_params = BatchTransferParams {
+        nft_ids: token_ids,
+        recipient: recipient.clone(),
+        uris: tokens
+            .iter()
+            .map(|token| token.token_uri.clone().unwrap_or_default())
+            .collect(),
     };
+    let encoded_payload: Vec<u8> = CrossChainMessage::BatchTransfer(transfer_params)
+        .encode_payload(route.chain_type, route.payload_version)?;
+    send_crosschain(
+        deps,
+        &env,
+        &info,
+        dst_chain_id,
+        route,
+        recipient,
+        tokens,
+        encoded_payload,
+        request_metadata,
+    )
+}
+
+/// Burns or locks tokens already checked by `check_transfer`.
+fn take_tokens(
+    deps: DepsMut,
+    env: &Env,
+    token_ids: &[u64],
+) -> Result<Vec<TransferredToken>, ContractError> {
+    let tract = Cw721NFTContract::default();
+    let mut tokens = vec![];
+    for token_id in token_ids {
+        let rider_info = tract.tokens.load(deps.storage, &token_id.to_string())?;
+        // given back if the ack reports a failure
+        send_token(deps.storage, env, *token_id, &rider_info)?;
+        tokens.push(TransferredToken {
+            token_id: *token_id,
+            owner: rider_info.owner.to_string(),
+            token_uri: rider_info.token_uri,
+        });
+    }
+    Ok(tokens)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn send_crosschain(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    dst_chain_id: String,
+    route: Route,
+    recipient: String,
+    tokens: Vec<TransferredToken>,
+    encoded_payload: Vec<u8>,
+    request_metadata: RequestMetaData,
+) -> Result<Response<RouterMsg>, ContractError> {
+    OUTBOUND_IN_FLIGHT.save(
+        deps.storage,
+        &TransferRecord {
+            // set from the `CrosschainCall` reply
+            request_identifier: 0,
+            tokens,
+            sender: info.sender.to_string(),
+            dst_chain_id: dst_chain_id.clone(),
+            recipient,
+            created_at: env.block.time,
+            created_at_height: env.block.height,
+            status: TransferStatus::Pending,
+        },
+    )?;
 
-    let encoded_payload: Vec<u8> = encode(&[transfer_params.get_evm_encoding()?]);
     let request_packet: Bytes = encode(&[
-        Token::String(dst_contract_add),
+        Token::String(route.remote_address),
         Token::Bytes(encoded_payload),
     ]);
 
@@ -127,58 +702,115 @@ pub fn transfer_crosschain(
         version: 1,
         route_amount: Uint128::new(0u128),
         route_recipient: String::from(""),
-        dest_chain_id: dst_chain_id.clone(),
+        dest_chain_id: dst_chain_id,
         request_metadata: request_metadata.get_abi_encoded_bytes(),
         request_packet,
     };
 
-    Ok(Response::new().add_message(i_send_request))
+    Ok(Response::new().add_submessage(SubMsg::reply_on_success(
+        i_send_request,
+        CREATE_OUTBOUND_REPLY_ID,
+    )))
 }
 
-pub fn ed25519_verify(message: &[u8], signature: [u8; 64], public_key: [u8; 32]) -> bool {
-    VerificationKey::try_from(public_key)
-        .and_then(|vk| vk.verify(&Signature::from(signature), message)).is_ok()
+fn token_ids_attribute(tokens: &[TransferredToken]) -> String {
+    tokens
+        .iter()
+        .map(|token| token.token_id.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
-fn verify_sign(msg: &String, sign: &String, public_key: &String) -> bool {
-    let msg = msg.as_bytes().try_into();
-    if msg.is_err() {
-        return false;
-    }
-    let signature = hex::decode(sign).unwrap().as_slice().try_into();
-    if signature.is_err() {
-        return false;
+/// Every signature must come from a distinct active signer and verify,
+/// and there must be at least `SIGNER_THRESHOLD` of them.
+fn verify_voucher_signatures(
+    deps: Deps,
+    digest: &[u8; 32],
+    signatures: &[VoucherSignature],
+) -> Result<(), ContractError> {
+    let mut key_ids: Vec<&str> = vec![];
+    for voucher_signature in signatures {
+        if key_ids.contains(&voucher_signature.key_id.as_str()) {
+            return Err(ContractError::DuplicateSigner {
+                key_id: voucher_signature.key_id.clone(),
+            });
+        }
+        key_ids.push(&voucher_signature.key_id);
+
+        let signer = match SIGNERS.may_load(deps.storage, voucher_signature.key_id.clone())? {
+            Some(signer) if signer.revoked_at.is_none() => signer,
+            _ => {
+                return Err(ContractError::UnknownSigner {
+                    key_id: voucher_signature.key_id.clone(),
+                })
+            }
+        };
+        verify_signature(
+            deps.api,
+            &signer.scheme,
+            digest,
+            &voucher_signature.signature,
+            &signer.public_key,
+        )?;
     }
-    let public_key = hex::decode(public_key).unwrap().as_slice().try_into();
-    if public_key.is_err() {
-        return false;
+
+    let threshold = SIGNER_THRESHOLD.load(deps.storage)?;
+    if key_ids.len() < threshold as usize {
+        return Err(ContractError::NotEnoughSignatures {
+            got: key_ids.len() as u32,
+            required: threshold,
+        });
     }
-    ed25519_verify( 
-        msg.unwrap(),
-        signature.unwrap(), 
-        public_key.unwrap()
-    )
+    Ok(())
 }
 
 pub fn mint_token(
-    deps: DepsMut,
-    _env: Env,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     token_uri: String,
-    signature: String
-) -> StdResult<Response<RouterMsg>> {
-    if ALREADY_MINTED.load(deps.storage, info.sender.to_string().clone()).unwrap_or(false) {
-        return Err(StdError::GenericErr {
-            msg: "Token already minted".to_string(),
-        });
+    nonce: u64,
+    expires: Expiration,
+    signatures: Vec<VoucherSignature>,
+) -> Result<Response<RouterMsg>, ContractError> {
+    ensure_not_paused(deps.as_ref())?;
+    let minted = MINT_COUNT
+        .may_load(deps.storage, info.sender.to_string())?
+        .unwrap_or_default();
+    if let Some(limit) = MINT_POLICY.load(deps.storage)?.per_address_limit() {
+        if minted >= limit {
+            return Err(ContractError::MintLimitReached {});
+        }
     }
 
-    if !verify_sign(&token_uri, &signature, &PUBLIC_KEY.load(deps.storage)?) {
-        return Err(StdError::GenericErr {
-            msg: "Signature not match".to_string(),
-        });
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::VoucherExpired {});
     }
 
+    let voucher = MintVoucher {
+        contract_address: env.contract.address.to_string(),
+        chain_id: env.block.chain_id.clone(),
+        minter: info.sender.to_string(),
+        token_uri: token_uri.clone(),
+        nonce,
+        expires,
+    };
+    let digest = voucher.digest();
+    let signature_hashes = signatures
+        .iter()
+        .map(|voucher_signature| signature_hash(&voucher_signature.signature))
+        .collect::<Result<Vec<_>, _>>()?;
+    for hash in &signature_hashes {
+        if CONSUMED_VOUCHERS.has(deps.storage, hash.clone()) {
+            return Err(ContractError::VoucherAlreadyConsumed {});
+        }
+    }
+    if USED_NONCES.has(deps.storage, (info.sender.to_string(), nonce)) {
+        return Err(ContractError::NonceAlreadyUsed { nonce });
+    }
+
+    verify_voucher_signatures(deps.as_ref(), &digest, &signatures)?;
+
     // create the token
     let token = TokenInfo {
         owner: info.sender.clone(),
@@ -187,35 +819,218 @@ pub fn mint_token(
         extension: Empty {},
     };
     let tract = Cw721NFTContract::default();
-    tract.increment_tokens(deps.storage).unwrap().to_string();
-    let token_id = TOTAL_SUPPLY.load(deps.storage)?;
-    let res = tract
+    tract.increment_tokens(deps.storage)?;
+    let token_id = next_local_token_id(deps.branch())?;
+    tract
         .tokens
-        .update(deps.storage, &token_id.to_string(), |old| match old {
-            Some(_) => Err(ContractError::Claimed {}),
-            None => Ok(token),
-        });
+        .save(deps.storage, &token_id.to_string(), &token)?;
 
-    if let Err(err) = res {
-        return Err(StdError::GenericErr {
-            msg: err.to_string(),
-        });
+    MINT_COUNT.save(deps.storage, info.sender.to_string(), &(minted + 1))?;
+    USED_NONCES.save(deps.storage, (info.sender.to_string(), nonce), &true)?;
+    for hash in signature_hashes {
+        CONSUMED_VOUCHERS.save(deps.storage, hash, &token_id)?;
     }
 
-    TOTAL_SUPPLY.save(deps.storage, &(token_id + 1))?;
-    ALREADY_MINTED.save(deps.storage, info.sender.to_string(), &true)?;
-
     Ok(Response::<RouterMsg>::new()
         .add_attribute("action", "mint")
-        .add_attribute("minter", info.sender.clone())
+        .add_attribute("minter", info.sender)
         .add_attribute("token_id", token_id.to_string()))
 }
 
+pub fn add_signer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    key_id: String,
+    public_key: String,
+    scheme: SignatureScheme,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::SignerManager)?;
+    save_new_signer(deps, &env, key_id.clone(), public_key, scheme)?;
+    Ok(Response::new()
+        .add_attribute("action", "add_signer")
+        .add_attribute("key_id", key_id))
+}
+
+pub fn revoke_signer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    key_id: String,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::SignerManager)?;
+    revoke_active_signer(deps.branch(), &env, key_id.clone())?;
+    let active = active_signer_count(deps.as_ref())?;
+    let threshold = SIGNER_THRESHOLD.load(deps.storage)?;
+    if active < threshold {
+        return Err(ContractError::SignersBelowThreshold { active, threshold });
+    }
+    Ok(Response::new()
+        .add_attribute("action", "revoke_signer")
+        .add_attribute("key_id", key_id))
+}
+
+pub fn rotate_signer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    key_id: String,
+    new_key_id: String,
+    public_key: String,
+    scheme: SignatureScheme,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::SignerManager)?;
+    revoke_active_signer(deps.branch(), &env, key_id.clone())?;
+    save_new_signer(deps, &env, new_key_id.clone(), public_key, scheme)?;
+    Ok(Response::new()
+        .add_attribute("action", "rotate_signer")
+        .add_attribute("key_id", key_id)
+        .add_attribute("new_key_id", new_key_id))
+}
+
+pub fn set_signer_threshold(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    threshold: u32,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::SignerManager)?;
+    let active = active_signer_count(deps.as_ref())?;
+    if threshold == 0 || threshold > active {
+        return Err(ContractError::InvalidThreshold { threshold, active });
+    }
+    SIGNER_THRESHOLD.save(deps.storage, &threshold)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_signer_threshold")
+        .add_attribute("threshold", threshold.to_string()))
+}
+
+pub fn update_collection_info(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    name: Option<String>,
+    symbol: Option<String>,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::Admin)?;
+    let tract = Cw721NFTContract::default();
+    let mut contract_info = tract.contract_info.load(deps.storage)?;
+    if let Some(name) = name {
+        contract_info.name = name;
+    }
+    if let Some(symbol) = symbol {
+        contract_info.symbol = symbol;
+    }
+    tract.contract_info.save(deps.storage, &contract_info)?;
+    Ok(Response::new()
+        .add_attribute("action", "update_collection_info")
+        .add_attribute("name", contract_info.name)
+        .add_attribute("symbol", contract_info.symbol))
+}
+
+pub fn update_minter(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    minter: String,
+) -> Result<Response<RouterMsg>, ContractError> {
+    only_role(deps.as_ref(), info, Role::Admin)?;
+    let minter = deps.api.addr_validate(&minter)?;
+    Cw721NFTContract::default()
+        .minter
+        .save(deps.storage, &minter)?;
+    Ok(Response::new()
+        .add_attribute("action", "update_minter")
+        .add_attribute("minter", minter))
+}
+
+/// Admin minted ids must be numeric and inside our namespace, so that they can
+/// be bridged and never clash with an id minted by another deployment.
+fn check_local_token_id(storage: &dyn Storage, token_id: &str) -> Result<(), ContractError> {
+    let namespace = TOKEN_ID_NAMESPACE.may_load(storage)?.unwrap_or_default();
+    let id = token_id
+        .parse::<u64>()
+        .map_err(|_| ContractError::InvalidTokenId {
+            token_id: token_id.to_string(),
+        })?;
+    if id >> TOKEN_ID_SEQUENCE_BITS != u64::from(namespace) {
+        return Err(ContractError::ForeignTokenId {
+            token_id: id,
+            namespace,
+        });
+    }
+    Ok(())
+}
+
+/// Takes the next id of our namespace, skipping ids a bridged token already holds.
+fn next_local_token_id(deps: DepsMut) -> Result<u64, ContractError> {
+    let namespace = TOKEN_ID_NAMESPACE
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let tokens = Cw721NFTContract::default().tokens;
+    let mut sequence = TOTAL_SUPPLY.load(deps.storage)?;
+    loop {
+        if sequence >> TOKEN_ID_SEQUENCE_BITS != 0 {
+            return Err(ContractError::TokenIdsExhausted {});
+        }
+        let token_id = namespaced_token_id(namespace, sequence);
+        sequence += 1;
+        if !tokens.has(deps.storage, &token_id.to_string()) {
+            TOTAL_SUPPLY.save(deps.storage, &sequence)?;
+            return Ok(token_id);
+        }
+    }
+}
+
+fn active_signer_count(deps: Deps) -> StdResult<u32> {
+    let mut count = 0;
+    for item in SIGNERS.range(deps.storage, None, None, Order::Ascending) {
+        if item?.1.revoked_at.is_none() {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+fn save_new_signer(
+    deps: DepsMut,
+    env: &Env,
+    key_id: String,
+    public_key: String,
+    scheme: SignatureScheme,
+) -> Result<(), ContractError> {
+    if SIGNERS.has(deps.storage, key_id.clone()) {
+        return Err(ContractError::SignerAlreadyExists { key_id });
+    }
+    let signer = SignerKey {
+        public_key: decode_public_key(&scheme, &public_key)?,
+        scheme,
+        added_at: env.block.time,
+        added_at_height: env.block.height,
+        revoked_at: None,
+    };
+    SIGNERS.save(deps.storage, key_id, &signer)?;
+    Ok(())
+}
+
+fn revoke_active_signer(deps: DepsMut, env: &Env, key_id: String) -> Result<(), ContractError> {
+    let mut signer = match SIGNERS.may_load(deps.storage, key_id.clone())? {
+        Some(signer) => signer,
+        None => return Err(ContractError::UnknownSigner { key_id }),
+    };
+    if signer.revoked_at.is_some() {
+        return Err(ContractError::SignerAlreadyRevoked { key_id });
+    }
+    signer.revoked_at = Some(env.block.time);
+    SIGNERS.save(deps.storage, key_id, &signer)?;
+    Ok(())
+}
+
 pub fn handle_sudo(
     deps: DepsMut<RouterQuery>,
     env: Env,
     msg: SudoMsg,
-) -> StdResult<Response<RouterMsg>> {
+) -> Result<Response<RouterMsg>, ContractError> {
     match msg {
         SudoMsg::HandleIReceive {
             request_sender,
@@ -231,62 +1046,374 @@ pub fn handle_sudo(
             payload,
         ),
         SudoMsg::HandleIAck {
-            request_identifier: _,
-            exec_flag: _,
+            request_identifier,
+            exec_flag,
             exec_data: _,
             refund_amount: _,
-        } => Ok(Response::new()),
+        } => handle_sudo_ack(deps, env, request_identifier, exec_flag),
     }
 }
 
-pub fn handle_sudo_request(
+/// Burns the outgoing token, or locks it in the contract in `LockRelease` mode.
+fn send_token(
+    storage: &mut dyn Storage,
+    env: &Env,
+    token_id: u64,
+    token: &TokenInfo<Empty>,
+) -> Result<(), ContractError> {
+    let tract = Cw721NFTContract::default();
+    match BRIDGE_MODE.may_load(storage)?.unwrap_or_default() {
+        BridgeMode::BurnMint => {
+            tract.tokens.remove(storage, &token_id.to_string())?;
+            tract.decrement_tokens(storage)?;
+        }
+        BridgeMode::LockRelease => {
+            let locked = TokenInfo {
+                owner: env.contract.address.clone(),
+                approvals: vec![],
+                token_uri: token.token_uri.clone(),
+                extension: Empty {},
+            };
+            tract.tokens.save(storage, &token_id.to_string(), &locked)?;
+            ESCROWED.save(storage, token_id, &token.owner.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Releases an escrowed token to `owner`, or mints it when this deployment
+/// does not hold it.
+fn receive_token(
+    storage: &mut dyn Storage,
+    token_id: u64,
+    owner: Addr,
+    token_uri: Option<String>,
+) -> Result<(), ContractError> {
+    let tract = Cw721NFTContract::default();
+    if ESCROWED.has(storage, token_id) {
+        ESCROWED.remove(storage, token_id);
+        let mut token = tract.tokens.load(storage, &token_id.to_string())?;
+        token.owner = owner;
+        tract.tokens.save(storage, &token_id.to_string(), &token)?;
+        return Ok(());
+    }
+    // the home deployment only mints its own namespace, other tokens never left it
+    if BRIDGE_MODE.may_load(storage)?.unwrap_or_default() == BridgeMode::LockRelease {
+        let namespace = TOKEN_ID_NAMESPACE.may_load(storage)?.unwrap_or_default();
+        if token_id >> TOKEN_ID_SEQUENCE_BITS != u64::from(namespace) {
+            return Err(ContractError::NotEscrowed { token_id });
+        }
+    }
+
+    let token = TokenInfo {
+        owner,
+        approvals: vec![],
+        token_uri,
+        extension: Empty {},
+    };
+    // ids are unique per namespace, a clash means a misconfigured deployment
+    // and the request fails rather than overwriting the existing token
+    tract
+        .tokens
+        .update(storage, &token_id.to_string(), |old| match old {
+            Some(_) => Err(ContractError::TokenIdCollision { token_id }),
+            None => Ok(token),
+        })?;
+    tract.increment_tokens(storage)?;
+    Ok(())
+}
+
+/// Keys the in-flight transfer by the request identifier Router assigned to it.
+pub fn handle_reply(
+    deps: DepsMut,
+    _env: Env,
+    msg: Reply,
+) -> Result<Response<RouterMsg>, ContractError> {
+    if msg.id != CREATE_OUTBOUND_REPLY_ID {
+        return Err(ContractError::UnknownReplyId { id: msg.id });
+    }
+    let request_identifier = crosschain_request_identifier(msg.result)
+        .map_err(|reason| ContractError::InvalidReply { reason })?;
+    let mut transfer = OUTBOUND_IN_FLIGHT.load(deps.storage)?;
+    OUTBOUND_IN_FLIGHT.remove(deps.storage);
+    transfer.request_identifier = request_identifier;
+    OUTBOUND_TRANSFERS.save(deps.storage, transfer.request_identifier, &transfer)?;
+    TRANSFERS_BY_SENDER.save(
+        deps.storage,
+        (transfer.sender.clone(), transfer.request_identifier),
+        &true,
+    )?;
+    for token in &transfer.tokens {
+        TRANSFERS_BY_TOKEN.save(
+            deps.storage,
+            (token.token_id, transfer.request_identifier),
+            &true,
+        )?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "transfer_crosschain")
+        .add_attribute(
+            "request_identifier",
+            transfer.request_identifier.to_string(),
+        )
+        .add_attribute("token_ids", token_ids_attribute(&transfer.tokens)))
+}
+
+pub fn handle_sudo_ack(
     deps: DepsMut<RouterQuery>,
     _env: Env,
+    request_identifier: u64,
+    exec_flag: bool,
+) -> Result<Response<RouterMsg>, ContractError> {
+    let mut transfer = match OUTBOUND_TRANSFERS.may_load(deps.storage, request_identifier)? {
+        Some(transfer) => transfer,
+        None => return Err(ContractError::UnknownRequest { request_identifier }),
+    };
+    if transfer.status != TransferStatus::Pending {
+        return Err(ContractError::TransferAlreadySettled { request_identifier });
+    }
+
+    let response = Response::new()
+        .add_attribute("action", "handle_ack")
+        .add_attribute("request_identifier", request_identifier.to_string())
+        .add_attribute("token_ids", token_ids_attribute(&transfer.tokens));
+    if exec_flag {
+        transfer.status = TransferStatus::Delivered;
+        OUTBOUND_TRANSFERS.save(deps.storage, request_identifier, &transfer)?;
+        return Ok(response.add_attribute("status", "delivered"));
+    }
+
+    // delivery failed, give the tokens back
+    for token in &transfer.tokens {
+        let owner = deps.api.addr_validate(&token.owner)?;
+        receive_token(deps.storage, token.token_id, owner, token.token_uri.clone())?;
+    }
+    transfer.status = TransferStatus::Refunded;
+    OUTBOUND_TRANSFERS.save(deps.storage, request_identifier, &transfer)?;
+    Ok(response.add_attribute("status", "refunded"))
+}
+
+pub fn handle_sudo_request(
+    deps: DepsMut<RouterQuery>,
+    env: Env,
     request_sender: String,
     src_chain_id: String,
-    _request_identifier: u64,
+    request_identifier: u64,
     payload: Binary,
-) -> StdResult<Response<RouterMsg>> {
-    let remote_contract_add = REMOTE_CONTRACT_MAPPING
-        .load(deps.storage, src_chain_id.clone())
-        .unwrap();
-
-    if remote_contract_add != request_sender {
-        return Err(StdError::GenericErr {
-            msg: "Invalid sender".to_string(),
-        });
+) -> Result<Response<RouterMsg>, ContractError> {
+    check_request_sender(
+        deps.storage,
+        &REMOTE_CONTRACT_MAPPING,
+        &src_chain_id,
+        &request_sender,
+    )?;
+    ensure_chain_enabled(deps.storage, &src_chain_id)?;
+
+    let response = inbound_ack_response(&src_chain_id, request_identifier);
+    // a redelivered transfer must not mint or release its tokens again
+    if !record_inbound_request(
+        deps.storage,
+        &PROCESSED_REQUESTS,
+        &src_chain_id,
+        request_identifier,
+    )? {
+        return Ok(response.add_attribute("status", "duplicate"));
     }
 
-    let params = TransferParams::get_params_types();
-    let param_vec: Vec<ParamType> = vec![params];
-    let token_vec = match decode(&param_vec, &payload.0) {
-        Ok(data) => data,
-        Err(_) => {
-            return Err(StdError::GenericErr {
-                msg: String::from("err.into()"),
+    // token recipients are addresses of this chain, typed like a cosmos chain unless set
+    let chain_type = chain_type_of(
+        deps.storage,
+        &CHAIN_TYPES,
+        &env.block.chain_id,
+        ChainType::ChainTypeCosmos,
+    )?;
+    match decode_message(&payload, chain_type)? {
+        CrossChainMessage::Transfer(transfer_params) => receive_transfer(
+            deps,
+            response,
+            &src_chain_id,
+            BatchTransferParams {
+                nft_ids: vec![transfer_params.nft_id],
+                recipient: transfer_params.recipient,
+                uris: vec![transfer_params.uri],
+            },
+        ),
+        CrossChainMessage::BatchTransfer(transfer_params) => {
+            receive_transfer(deps, response, &src_chain_id, transfer_params)
+        }
+        CrossChainMessage::MetadataUpdate(update) => {
+            // tokens that are not on this chain have nothing to update
+            let tract = Cw721NFTContract::default();
+            let token_id = update.nft_id.to_string();
+            let response = response
+                .add_attribute("message_type", "metadata_update")
+                .add_attribute("token_id", token_id.clone());
+            let mut token = match tract.tokens.may_load(deps.storage, &token_id)? {
+                Some(token) => token,
+                None => {
+                    return Ok(response
+                        .add_attribute("status", "skipped")
+                        .add_attribute("reason", "unknown_token"))
+                }
+            };
+            // a chain only updates the tokens it sent here
+            let origin = TOKEN_ORIGIN.may_load(deps.storage, update.nft_id)?;
+            if origin.as_deref() != Some(src_chain_id.as_str()) {
+                return Err(ContractError::NotTokenOrigin {
+                    token_id: update.nft_id,
+                    chain_id: src_chain_id,
+                });
+            }
+            token.token_uri = Some(update.uri);
+            tract.tokens.save(deps.storage, &token_id, &token)?;
+            Ok(response.add_attribute("status", "processed"))
+        }
+        CrossChainMessage::Admin(_) if !ADMIN_SOURCES.has(deps.storage, src_chain_id.clone()) => {
+            Err(ContractError::NotAdminSource {
+                chain_id: src_chain_id,
             })
         }
+        CrossChainMessage::Admin(AdminCommand::Pause) => {
+            PAUSED.save(deps.storage, &true)?;
+            Ok(response
+                .add_attribute("status", "processed")
+                .add_attribute("message_type", "admin")
+                .add_attribute("command", "pause"))
+        }
+        // a remote deployment may stop this one, only a local pauser lifts the pause
+        CrossChainMessage::Admin(AdminCommand::Unpause) => Err(ContractError::RemoteUnpause {}),
+    }
+}
+
+fn receive_transfer(
+    deps: DepsMut<RouterQuery>,
+    response: Response<RouterMsg>,
+    src_chain_id: &str,
+    transfer_params: BatchTransferParams,
+) -> Result<Response<RouterMsg>, ContractError> {
+    let recipient = deps.api.addr_validate(&transfer_params.recipient)?;
+    for (nft_id, uri) in transfer_params.nft_ids.iter().zip(transfer_params.uris) {
+        receive_token(deps.storage, *nft_id, recipient.clone(), Some(uri))?;
+        TOKEN_ORIGIN.save(deps.storage, *nft_id, &src_chain_id.to_string())?;
+    }
+
+    let token_ids: Vec<String> = transfer_params
+        .nft_ids
+        .iter()
+        .map(|nft_id| nft_id.to_string())
+        .collect();
+    Ok(response
+        .add_attribute("status", "processed")
+        .add_attribute("token_ids", token_ids.join(",")))
+}
+
+/// Decodes `payload` as `param`, only if it is exactly what encoding the
+/// result gives back. Tells the single and batch payloads apart.
+fn decode_exact(param: ParamType, payload: &[u8]) -> Option<Token> {
+    let tokens = decode(&[param], payload).ok()?;
+    if encode(&tokens) != payload {
+        return None;
+    }
+    tokens.into_iter().next()
+}
+
+/// Opens a versioned envelope, payloads sent before envelopes are read as
+/// transfers.
+fn decode_message(payload: &[u8], chain_type: u64) -> Result<CrossChainMessage, ContractError> {
+    let (version, message_type, body) = match split_envelope(payload).map_err(payload_decode_err)? {
+        Some(envelope) => envelope,
+        None => {
+            return decode_transfer_payload(payload, chain_type)
+                .map(CrossChainMessage::BatchTransfer)
+        }
     };
-    let transfer_params_tokens: Vec<Token> = token_vec[0].clone().into_tuple().unwrap();
-    let transfer_params: TransferParams = TransferParams::from_token_tuple(transfer_params_tokens)?;
+    if version != PAYLOAD_VERSION {
+        return Err(ContractError::UnsupportedPayloadVersion { version });
+    }
 
-    // mint nft
-    let tract = Cw721NFTContract::default();
-    let token_info = TokenInfo {
-        owner: deps.api.addr_validate(&transfer_params.recipient)?,
-        approvals: vec![],
-        token_uri: Some(transfer_params.uri),
-        extension: Empty {},
+    let decode_body = |param: ParamType| {
+        decode_exact(param, &body).ok_or_else(|| ContractError::PayloadDecode {
+            reason: format!("malformed body for message type {}", message_type),
+        })
     };
-    tract.tokens.save(
-        deps.storage,
-        &transfer_params.nft_id.to_string(),
-        &token_info,
-    )?;
-    tract.increment_tokens(deps.storage)?;
+    let decode_tuple = |param: ParamType| {
+        decode_body(param)?
+            .into_tuple()
+            .ok_or_else(|| ContractError::PayloadDecode {
+                reason: format!("malformed body for message type {}", message_type),
+            })
+    };
+    match message_type {
+        MESSAGE_TYPE_TRANSFER => {
+            let tuple = decode_tuple(TransferParams::get_params_types())?;
+            Ok(CrossChainMessage::Transfer(
+                TransferParams::from_token_tuple(tuple, chain_type).map_err(payload_decode_err)?,
+            ))
+        }
+        MESSAGE_TYPE_BATCH_TRANSFER => {
+            let tuple = decode_tuple(BatchTransferParams::get_params_types())?;
+            let transfer_params = BatchTransferParams::from_token_tuple(tuple, chain_type)
+                .map_err(payload_decode_err)?;
+            check_batch_lengths(&transfer_params)?;
+            Ok(CrossChainMessage::BatchTransfer(transfer_params))
+        }
+        MESSAGE_TYPE_METADATA_UPDATE => {
+            let tuple = decode_tuple(MetadataUpdateParams::get_params_types())?;
+            Ok(CrossChainMessage::MetadataUpdate(
+                MetadataUpdateParams::from_token_tuple(tuple).map_err(payload_decode_err)?,
+            ))
+        }
+        MESSAGE_TYPE_ADMIN => {
+            let token = decode_body(ParamType::Uint(8))?;
+            Ok(CrossChainMessage::Admin(
+                AdminCommand::from_token(token).map_err(payload_decode_err)?,
+            ))
+        }
+        _ => Err(ContractError::UnknownMessageType { message_type }),
+    }
+}
+
+fn payload_decode_err(err: StdError) -> ContractError {
+    ContractError::PayloadDecode {
+        reason: err.to_string(),
+    }
+}
 
-    let mut response = Response::new();
-    let encoded_ack_payload: Vec<u8> = encode(&[Token::String(src_chain_id)]);
-    response.data = Some(Binary(encoded_ack_payload));
-    Ok(response)
+fn check_batch_lengths(transfer_params: &BatchTransferParams) -> Result<(), ContractError> {
+    if transfer_params.nft_ids.len() != transfer_params.uris.len() {
+        return Err(ContractError::PayloadDecode {
+            reason: "token ids and uris differ in length".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Reads a `TransferParams` or `BatchTransferParams` payload sent before
+/// envelopes, a single transfer comes back as a batch of one.
+fn decode_transfer_payload(
+    payload: &[u8],
+    chain_type: u64,
+) -> Result<BatchTransferParams, ContractError> {
+    if let Some(tuple) =
+        decode_exact(TransferParams::get_params_types(), payload).and_then(Token::into_tuple)
+    {
+        let transfer_params =
+            TransferParams::from_token_tuple(tuple, chain_type).map_err(payload_decode_err)?;
+        return Ok(BatchTransferParams {
+            nft_ids: vec![transfer_params.nft_id],
+            recipient: transfer_params.recipient,
+            uris: vec![transfer_params.uri],
+        });
+    }
+    if let Some(tuple) =
+        decode_exact(BatchTransferParams::get_params_types(), payload).and_then(Token::into_tuple)
+    {
+        let transfer_params =
+            BatchTransferParams::from_token_tuple(tuple, chain_type).map_err(payload_decode_err)?;
+        check_batch_lengths(&transfer_params)?;
+        return Ok(transfer_params);
+    }
+    Err(ContractError::PayloadDecode {
+        reason: "not a transfer or batch transfer payload".to_string(),
+    })
 }
@@ -1,6 +1,10 @@
+#![allow(non_snake_case)]
+
 pub mod contract;
+pub mod error;
 pub mod execution;
 pub mod query;
+pub mod signature;
 pub mod state;
 
 pub use serde::{Deserialize, Serialize};
@@ -1,38 +1,285 @@
-use cosmwasm_std::{to_binary, Binary, Deps, Env, StdResult};
+use cosmwasm_std::{to_binary, Binary, Deps, Env, MessageInfo, Order, StdResult};
 use cw2::get_contract_version;
-use new_crosstalk_sample::xerc721::QueryMsg;
+use cw_storage_plus::Bound;
+use new_crosstalk_sample::xerc721::{
+    CheckTransferResponse, EscrowedToken, EscrowedTokensResponse, MintAllowanceResponse,
+    PendingOwnerResponse, QueryMsg, RemoteContractInfo, RemoteContractsResponse, RolesResponse,
+    SignerInfo, SignersResponse, TransfersResponse,
+};
+use router_wasm_bindings::types::RequestMetaData;
 
 use crate::{
-    execution::{Cw721NFTContract, Cw721QueryMsg},
-    state::{OWNER, REMOTE_CONTRACT_MAPPING, ALREADY_MINTED},
+    execution::{self, Cw721NFTContract, Cw721QueryMsg},
+    state::{
+        ADMIN_SOURCES, BRIDGE_MODE, CHAIN_TYPES, DISABLED_CHAINS, ESCROWED, MINT_COUNT,
+        MINT_POLICY, OUTBOUND_TRANSFERS, OWNER, PAUSED, PENDING_OWNER, PROCESSED_REQUESTS,
+        REMOTE_CONTRACT_MAPPING, ROLES, SIGNERS, SIGNER_THRESHOLD, TOKEN_ID_NAMESPACE,
+        TRANSFERS_BY_SENDER, TRANSFERS_BY_TOKEN,
+    },
 };
 
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
 pub fn handle_query(deps: Deps, env: Env, msg: Cw721QueryMsg) -> StdResult<Binary> {
     let tract = Cw721NFTContract::default();
     match msg {
         Cw721QueryMsg::Extension { msg } => match msg {
             QueryMsg::GetContractVersion {} => to_binary(&get_contract_version(deps.storage)?),
             QueryMsg::GetOwner {} => to_binary(&get_owner(deps)?),
+            QueryMsg::GetPendingOwner {} => to_binary(&get_pending_owner(deps)?),
             QueryMsg::GetRemoteContract { chain_id } => {
                 to_binary(&get_remote_contract(deps, chain_id)?)
-            },
-            QueryMsg::IsAlreadyMinted { owner } => to_binary(&is_already_mint(deps, owner)?),  
+            }
+            QueryMsg::ListRemoteContracts { start_after, limit } => {
+                to_binary(&list_remote_contracts(deps, start_after, limit)?)
+            }
+            QueryMsg::IsChainEnabled { chain_id } => to_binary(
+                &(REMOTE_CONTRACT_MAPPING.has(deps.storage, chain_id.clone())
+                    && !DISABLED_CHAINS.has(deps.storage, chain_id)),
+            ),
+            QueryMsg::GetChainType { chain_id } => {
+                to_binary(&CHAIN_TYPES.may_load(deps.storage, chain_id)?)
+            }
+            QueryMsg::GetPayloadVersion { chain_id } => {
+                to_binary(&execution::payload_version_of(deps.storage, &chain_id)?)
+            }
+            QueryMsg::IsAdminSource { chain_id } => {
+                to_binary(&ADMIN_SOURCES.has(deps.storage, chain_id))
+            }
+            QueryMsg::IsAlreadyMinted { owner } => to_binary(&is_already_mint(deps, owner)?),
+            QueryMsg::MintAllowance { owner } => to_binary(&get_mint_allowance(deps, owner)?),
+            QueryMsg::ListSigners { start_after, limit } => {
+                to_binary(&list_signers(deps, start_after, limit)?)
+            }
+            QueryMsg::GetSignerThreshold {} => to_binary(&SIGNER_THRESHOLD.load(deps.storage)?),
+            QueryMsg::RolesOf { address } => to_binary(&roles_of(deps, address)?),
+            QueryMsg::IsPaused {} => to_binary(&is_paused(deps)?),
+            QueryMsg::GetTransfer { request_identifier } => {
+                to_binary(&OUTBOUND_TRANSFERS.load(deps.storage, request_identifier)?)
+            }
+            QueryMsg::TransfersBySender {
+                sender,
+                start_after,
+                limit,
+            } => to_binary(&transfers_by_sender(deps, sender, start_after, limit)?),
+            QueryMsg::TransfersByToken {
+                token_id,
+                start_after,
+                limit,
+            } => to_binary(&transfers_by_token(deps, token_id, start_after, limit)?),
+            QueryMsg::GetTokenIdNamespace {} => to_binary(
+                &TOKEN_ID_NAMESPACE
+                    .may_load(deps.storage)?
+                    .unwrap_or_default(),
+            ),
+            QueryMsg::GetBridgeMode {} => {
+                to_binary(&BRIDGE_MODE.may_load(deps.storage)?.unwrap_or_default())
+            }
+            QueryMsg::ListEscrowed { start_after, limit } => {
+                to_binary(&list_escrowed(deps, start_after, limit)?)
+            }
+            QueryMsg::CheckTransfer {
+                sender,
+                dst_chain_id,
+                token_ids,
+                recipient,
+                request_metadata,
+            } => to_binary(&check_transfer(
+                deps,
+                env,
+                sender,
+                dst_chain_id,
+                token_ids,
+                recipient,
+                request_metadata,
+            )?),
+            QueryMsg::IsRequestProcessed {
+                src_chain_id,
+                request_identifier,
+            } => {
+                to_binary(&PROCESSED_REQUESTS.has(deps.storage, (src_chain_id, request_identifier)))
+            }
         },
         _ => tract.query(deps, env, msg),
     }
 }
 
-pub fn get_owner(deps: Deps) -> StdResult<String> {
-    OWNER.load(deps.storage)
+pub fn get_owner(deps: Deps) -> StdResult<Option<String>> {
+    OWNER.may_load(deps.storage)
+}
+
+fn get_pending_owner(deps: Deps) -> StdResult<PendingOwnerResponse> {
+    let pending = PENDING_OWNER.may_load(deps.storage)?;
+    Ok(PendingOwnerResponse {
+        pending_owner: pending.as_ref().map(|pending| pending.owner.clone()),
+        expires: pending.map(|pending| pending.expires),
+    })
 }
 
 fn get_remote_contract(deps: Deps, chain_id: String) -> StdResult<String> {
     REMOTE_CONTRACT_MAPPING.load(deps.storage, chain_id)
 }
 
+fn list_remote_contracts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<RemoteContractsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let contracts = REMOTE_CONTRACT_MAPPING
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (chain_id, remote_address) = item?;
+            Ok(RemoteContractInfo {
+                chain_type: CHAIN_TYPES.may_load(deps.storage, chain_id.clone())?,
+                enabled: !DISABLED_CHAINS.has(deps.storage, chain_id.clone()),
+                payload_version: execution::payload_version_of(deps.storage, &chain_id)?,
+                chain_id,
+                remote_address,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(RemoteContractsResponse { contracts })
+}
+
 fn is_already_mint(deps: Deps, owner: String) -> StdResult<bool> {
-    match ALREADY_MINTED.load(deps.storage, owner) {
-        Ok(res) => Ok(res),
-        Err(_) => Ok(false)
-    }
+    Ok(MINT_COUNT
+        .may_load(deps.storage, owner)?
+        .unwrap_or_default()
+        > 0)
+}
+
+fn get_mint_allowance(deps: Deps, owner: String) -> StdResult<MintAllowanceResponse> {
+    let minted = MINT_COUNT
+        .may_load(deps.storage, owner)?
+        .unwrap_or_default();
+    let remaining = MINT_POLICY
+        .load(deps.storage)?
+        .per_address_limit()
+        .map(|limit| limit.saturating_sub(minted));
+    Ok(MintAllowanceResponse { minted, remaining })
+}
+
+fn roles_of(deps: Deps, address: String) -> StdResult<RolesResponse> {
+    let roles = ROLES.may_load(deps.storage, address)?.unwrap_or_default();
+    Ok(RolesResponse { roles })
+}
+
+pub fn is_paused(deps: Deps) -> StdResult<bool> {
+    Ok(PAUSED.may_load(deps.storage)?.unwrap_or_default())
+}
+
+fn load_transfers(
+    deps: Deps,
+    request_identifiers: impl Iterator<Item = StdResult<u64>>,
+) -> StdResult<TransfersResponse> {
+    let transfers = request_identifiers
+        .map(|request_identifier| OUTBOUND_TRANSFERS.load(deps.storage, request_identifier?))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(TransfersResponse { transfers })
+}
+
+fn transfers_by_sender(
+    deps: Deps,
+    sender: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<TransfersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let request_identifiers = TRANSFERS_BY_SENDER
+        .prefix(sender)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit);
+    load_transfers(deps, request_identifiers)
+}
+
+fn transfers_by_token(
+    deps: Deps,
+    token_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<TransfersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let request_identifiers = TRANSFERS_BY_TOKEN
+        .prefix(token_id)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit);
+    load_transfers(deps, request_identifiers)
+}
+
+fn check_transfer(
+    deps: Deps,
+    env: Env,
+    sender: String,
+    dst_chain_id: String,
+    token_ids: Vec<u64>,
+    recipient: String,
+    request_metadata: RequestMetaData,
+) -> StdResult<CheckTransferResponse> {
+    let info = MessageInfo {
+        sender: deps.api.addr_validate(&sender)?,
+        funds: vec![],
+    };
+    let checked = execution::check_transfer(
+        deps,
+        &env,
+        &info,
+        &dst_chain_id,
+        &token_ids,
+        &recipient,
+        &request_metadata,
+    );
+    Ok(CheckTransferResponse {
+        ok: checked.is_ok(),
+        error: checked.err().map(|err| err.to_string()),
+    })
+}
+
+fn list_escrowed(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EscrowedTokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let tokens = ESCROWED
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (token_id, owner) = item?;
+            Ok(EscrowedToken { token_id, owner })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(EscrowedTokensResponse { tokens })
+}
+
+fn list_signers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SignersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let signers = SIGNERS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key_id, signer) = item?;
+            Ok(SignerInfo {
+                key_id,
+                public_key: hex::encode(signer.public_key.as_slice()),
+                scheme: signer.scheme,
+                active: signer.revoked_at.is_none(),
+                added_at: signer.added_at,
+                added_at_height: signer.added_at_height,
+                revoked_at: signer.revoked_at,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(SignersResponse { signers })
 }
@@ -0,0 +1,174 @@
+use cosmwasm_std::{Api, Binary};
+use ed25519_zebra::{Signature, VerificationKey};
+use new_crosstalk_sample::common::{decode_ed25519_public_key, PublicKeyError};
+use new_crosstalk_sample::xerc721::SignatureScheme;
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+// what `personal_sign` / `signMessage` prepends to a 32 byte message
+const EIP191_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n32";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SignatureError {
+    #[error("Malformed public key: {reason}")]
+    MalformedPublicKey { reason: String },
+
+    #[error("Invalid public key length: expected {expected} bytes, got {actual}")]
+    InvalidPublicKeyLength { expected: String, actual: usize },
+
+    #[error("Malformed signature: {reason}")]
+    MalformedSignature { reason: String },
+
+    #[error("Invalid signature length: expected {expected} bytes, got {actual}")]
+    InvalidSignatureLength { expected: String, actual: usize },
+
+    #[error("Signature verification failed")]
+    VerificationFailed {},
+}
+
+impl From<PublicKeyError> for SignatureError {
+    fn from(err: PublicKeyError) -> Self {
+        match err {
+            PublicKeyError::Malformed { reason } => SignatureError::MalformedPublicKey { reason },
+            PublicKeyError::InvalidLength { expected, actual } => {
+                SignatureError::InvalidPublicKeyLength { expected, actual }
+            }
+        }
+    }
+}
+
+pub fn ed25519_verify(message: &[u8], signature: [u8; 64], public_key: [u8; 32]) -> bool {
+    VerificationKey::try_from(public_key)
+        .and_then(|vk| vk.verify(&Signature::from(signature), message))
+        .is_ok()
+}
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// The hash an Ethereum wallet signs for `signMessage(digest)`.
+pub fn eip191_hash(digest: &[u8; 32]) -> [u8; 32] {
+    let mut message = EIP191_PREFIX.to_vec();
+    message.extend_from_slice(digest);
+    keccak256(&message)
+}
+
+/// Last 20 bytes of the keccak256 of an uncompressed public key.
+pub fn ethereum_address(uncompressed_public_key: &[u8]) -> Vec<u8> {
+    keccak256(&uncompressed_public_key[1..])[12..].to_vec()
+}
+
+/// `public_key` is either a 33/65 byte secp256k1 key, checked against the
+/// 64 byte `r || s` part of the signature, or a 20 byte Ethereum address,
+/// which needs the recovery byte of a 65 byte `r || s || v` signature.
+pub fn secp256k1_eip191_verify(
+    api: &dyn Api,
+    digest: &[u8; 32],
+    signature: &[u8],
+    public_key: &[u8],
+) -> bool {
+    if signature.len() != 64 && signature.len() != 65 {
+        return false;
+    }
+    let hash = eip191_hash(digest);
+    if public_key.len() != 20 {
+        return api
+            .secp256k1_verify(&hash, &signature[..64], public_key)
+            .unwrap_or(false);
+    }
+
+    let recovery_param = match signature.get(64) {
+        Some(v @ (0 | 1)) => *v,
+        Some(v @ (27 | 28)) => *v - 27,
+        _ => return false,
+    };
+    match api.secp256k1_recover_pubkey(&hash, &signature[..64], recovery_param) {
+        Ok(recovered) => ethereum_address(&recovered) == public_key,
+        Err(_) => false,
+    }
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, hex::FromHexError> {
+    hex::decode(value.trim_start_matches("0x"))
+}
+
+/// Decodes a hex signer key and checks it can be used with `scheme`.
+pub fn decode_public_key(
+    scheme: &SignatureScheme,
+    public_key: &str,
+) -> Result<Binary, SignatureError> {
+    if scheme == &SignatureScheme::Ed25519 {
+        return Ok(decode_ed25519_public_key(public_key)?);
+    }
+    let public_key = decode_hex(public_key).map_err(|err| SignatureError::MalformedPublicKey {
+        reason: err.to_string(),
+    })?;
+    if ![20, 33, 65].contains(&public_key.len()) {
+        return Err(SignatureError::InvalidPublicKeyLength {
+            expected: "20 (address), 33 or 65".to_string(),
+            actual: public_key.len(),
+        });
+    }
+    Ok(Binary(public_key))
+}
+
+/// sha256 of the `r || s` part of a hex signature, the same for a secp256k1
+/// signature sent with or without its recovery byte.
+pub fn signature_hash(signature: &str) -> Result<Vec<u8>, SignatureError> {
+    let signature = decode_hex(signature).map_err(|err| SignatureError::MalformedSignature {
+        reason: err.to_string(),
+    })?;
+    let rs = signature.get(..64).unwrap_or(&signature);
+    Ok(Sha256::digest(rs).to_vec())
+}
+
+/// Checks a hex `signature` of `digest` against a key from `decode_public_key`.
+pub fn verify_signature(
+    api: &dyn Api,
+    scheme: &SignatureScheme,
+    digest: &[u8; 32],
+    signature: &str,
+    public_key: &[u8],
+) -> Result<(), SignatureError> {
+    let signature = decode_hex(signature).map_err(|err| SignatureError::MalformedSignature {
+        reason: err.to_string(),
+    })?;
+    let verified = match scheme {
+        SignatureScheme::Ed25519 => {
+            let signature: [u8; 64] = signature.as_slice().try_into().map_err(|_| {
+                SignatureError::InvalidSignatureLength {
+                    expected: "64".to_string(),
+                    actual: signature.len(),
+                }
+            })?;
+            let public_key: [u8; 32] =
+                public_key
+                    .try_into()
+                    .map_err(|_| SignatureError::InvalidPublicKeyLength {
+                        expected: "32".to_string(),
+                        actual: public_key.len(),
+                    })?;
+            ed25519_verify(digest, signature, public_key)
+        }
+        SignatureScheme::Secp256k1 => {
+            // an address can only be matched through the recovery byte
+            let (valid_length, expected) = match public_key.len() {
+                20 => (signature.len() == 65, "65"),
+                _ => (signature.len() == 64 || signature.len() == 65, "64 or 65"),
+            };
+            if !valid_length {
+                return Err(SignatureError::InvalidSignatureLength {
+                    expected: expected.to_string(),
+                    actual: signature.len(),
+                });
+            }
+            secp256k1_eip191_verify(api, digest, &signature, public_key)
+        }
+    };
+    if !verified {
+        return Err(SignatureError::VerificationFailed {});
+    }
+    Ok(())
+}
@@ -1,9 +1,75 @@
+use cosmwasm_std::{Binary, Timestamp};
+use cw0::Expiration;
 use cw_storage_plus::{Item, Map};
+use new_crosstalk_sample::xerc721::{
+    BridgeMode, MintPolicy, Role, SignatureScheme, TransferRecord,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignerKey {
+    // raw key bytes, checked against `scheme` when the key is added
+    pub public_key: Binary,
+    #[serde(default)]
+    pub scheme: SignatureScheme,
+    pub added_at: Timestamp,
+    pub added_at_height: u64,
+    // set once the key is revoked, revoked keys are kept so their ids are never reused
+    pub revoked_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwner {
+    pub owner: String,
+    pub expires: Expiration,
+}
+
+// removed when ownership is renounced
 pub const OWNER: Item<String> = Item::new("owner");
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+// address => roles granted to it, the owner implicitly holds every role
+pub const ROLES: Map<String, Vec<Role>> = Map::new("roles");
+pub const PAUSED: Item<bool> = Item::new("paused");
 // chain chain id => address of our contract in bytes
 pub const REMOTE_CONTRACT_MAPPING: Map<String, String> = Map::new("remote_contract_mapping");
-// who has already minted
-pub const ALREADY_MINTED: Map<String, bool> = Map::new("already_minted");
+// chain id => true while transfers to and from the chain are stopped
+pub const DISABLED_CHAINS: Map<String, bool> = Map::new("disabled_chains");
+// chain id => Router `ChainType` code, decides how recipients on that chain are encoded
+pub const CHAIN_TYPES: Map<String, u64> = Map::new("chain_types");
+// chain id => payload version its remote contract reads, legacy when unset
+pub const PAYLOAD_VERSIONS: Map<String, u8> = Map::new("payload_versions");
+// chain id => true when its remote contract may send admin commands
+pub const ADMIN_SOURCES: Map<String, bool> = Map::new("admin_sources");
+// minter => number of tokens minted with vouchers
+pub const MINT_COUNT: Map<String, u64> = Map::new("mint_count");
+pub const MINT_POLICY: Item<MintPolicy> = Item::new("mint_policy");
+// sequence of the next locally minted token
 pub const TOTAL_SUPPLY: Item<u64> = Item::new("total_supply");
-pub const PUBLIC_KEY: Item<String> = Item::new("public_key");
+// missing on deployments instantiated before namespaces, which use 0
+pub const TOKEN_ID_NAMESPACE: Item<u32> = Item::new("token_id_namespace");
+// missing on deployments instantiated before bridge modes, which burn and mint
+pub const BRIDGE_MODE: Item<BridgeMode> = Item::new("bridge_mode");
+// token id => owner when it was locked, for tokens held by the contract in `LockRelease` mode
+pub const ESCROWED: Map<u64, String> = Map::new("escrowed");
+// signer key id => voucher signing key
+pub const SIGNERS: Map<String, SignerKey> = Map::new("signers");
+pub const SIGNER_THRESHOLD: Item<u32> = Item::new("signer_threshold");
+// (minter, nonce) => true once the voucher carrying this nonce has been used
+pub const USED_NONCES: Map<(String, u64), bool> = Map::new("used_nonces");
+// sha256 of a voucher signature => id of the token it minted
+pub const CONSUMED_VOUCHERS: Map<Vec<u8>, u64> = Map::new("consumed_vouchers");
+// layout of 1.0.0 deployments, moved to `SIGNERS` and `MINT_COUNT` by `migrate`
+pub const LEGACY_PUBLIC_KEY: Item<String> = Item::new("public_key");
+pub const LEGACY_ALREADY_MINTED: Map<String, bool> = Map::new("already_minted");
+// transfer whose `CrosschainCall` has not replied with its request identifier yet
+pub const OUTBOUND_IN_FLIGHT: Item<TransferRecord> = Item::new("outbound_in_flight");
+// Router request identifier => outbound transfer
+pub const OUTBOUND_TRANSFERS: Map<u64, TransferRecord> = Map::new("outbound_transfers");
+// (sender, request identifier) and (token id, request identifier) indexes of `OUTBOUND_TRANSFERS`
+pub const TRANSFERS_BY_SENDER: Map<(String, u64), bool> = Map::new("transfers_by_sender");
+pub const TRANSFERS_BY_TOKEN: Map<(u64, u64), bool> = Map::new("transfers_by_token");
+// token id => chain it last came in from, the only chain its metadata updates are taken from
+pub const TOKEN_ORIGIN: Map<u64, String> = Map::new("token_origin");
+// (source chain id, Router request identifier) => true once the inbound request was executed
+pub const PROCESSED_REQUESTS: Map<(String, u64), bool> = Map::new("processed_requests");
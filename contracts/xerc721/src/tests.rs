@@ -1,14 +1,38 @@
-use crate::contract::{execute, instantiate, query};
-use crate::execution::{Cw721ExecuteMsg, Cw721QueryMsg};
-use cw721::{NftInfoResponse, OwnerOfResponse};
-use new_crosstalk_sample::xerc721::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use router_wasm_bindings::types::RequestMetaData;
-use router_wasm_bindings::RouterMsg;
+use crate::contract::{execute, instantiate, migrate, query, reply, sudo};
+use crate::error::ContractError;
+use crate::execution::{Cw721ExecuteMsg, Cw721QueryMsg, CREATE_OUTBOUND_REPLY_ID};
+use crate::signature::SignatureError;
+use crate::state::{LEGACY_ALREADY_MINTED, LEGACY_PUBLIC_KEY, OWNER, TOTAL_SUPPLY};
+use cw0::Expiration;
+use cw721::{ContractInfoResponse, NftInfoResponse, OwnerOfResponse};
+use cw721_base::MinterResponse;
+use ed25519_zebra::{SigningKey, VerificationKey};
+use k256::ecdsa::{recoverable, signature::DigestSigner};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use new_crosstalk_sample::testing::{
+    self, ack_msg, crosschain_call_reply, transfer_request_metadata, with_router_deps,
+};
+use new_crosstalk_sample::xerc721::{
+    namespaced_token_id, split_envelope, AdminCommand, BatchTransferParams, BridgeMode,
+    ChainTypeInfo, CheckTransferResponse, CrossChainMessage, EscrowedToken, EscrowedTokensResponse,
+    ExecuteMsg, InstantiateMsg, MetadataUpdateParams, MigrateMsg, MintAllowanceResponse,
+    MintPolicy, MintVoucher, PendingOwnerResponse, QueryMsg, RemoteContract, RemoteContractInfo,
+    RemoteContractsResponse, Role, RolesResponse, SignatureScheme, SignersResponse, TransferParams,
+    TransferRecord, TransferStatus, TransferredToken, TransfersResponse, VoucherSignature,
+    DEFAULT_SIGNER_KEY_ID, LEGACY_PAYLOAD_VERSION, MAX_BATCH_SIZE, MAX_TOKEN_ID_NAMESPACE,
+    MESSAGE_TYPE_BATCH_TRANSFER, PAYLOAD_VERSION,
+};
+use router_wasm_bindings::ethabi::{decode, encode, ParamType, Token};
+use router_wasm_bindings::types::{AckType, ChainType, RequestMetaData};
+use router_wasm_bindings::{RouterMsg, SudoMsg};
+use sha3::{Digest, Keccak256};
 
 use cosmwasm_std::testing::{
     mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
 };
-use cosmwasm_std::{CosmosMsg, Deps, Empty, Env, MessageInfo, Response, StdError, Uint128};
+use cosmwasm_std::{
+    Addr, Attribute, Binary, CosmosMsg, Deps, Empty, Env, MessageInfo, Response, StdError, Uint128,
+};
 
 use cosmwasm_std::from_binary;
 use cosmwasm_std::DepsMut;
@@ -16,21 +40,152 @@ use cosmwasm_std::OwnedDeps;
 use std::marker::PhantomData;
 
 const SENDER: &str = "router1sxc6t9uh9u8f252gl3yqetlt6qmp2syx3v0p3w";
+const OTHER_SENDER: &str = "router1apapk9zfz3rp4x87fsm6h0s3zd0wlmkz0fx8tx";
+const TOKEN_URI: &str = "https://ipfs.io/ipfs/Qma49KCamwSberpE8whTz3ESk81PoGtLsimmj3YhsWJKPX";
+const OTHER_TOKEN_URI: &str = "https://ipfs.io/ipfs/QmakZtb3uKouz5QFGTaUvjN3akJDkAgGGSLh61aqEUnGt1";
+// test-only ed25519 seeds for the voucher signers
+const SIGNER_SEED: [u8; 32] = [7u8; 32];
+const OTHER_SIGNER_SEED: [u8; 32] = [9u8; 32];
+// test-only secp256k1 secret key of an Ethereum signer
+const ETH_SIGNER_SEED: [u8; 32] = [5u8; 32];
 
 fn get_mock_dependencies() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
     OwnedDeps {
         storage: MockStorage::default(),
         api: MockApi::default(),
         querier: MockQuerier::default(),
-        custom_query_type: PhantomData::default(),
+        custom_query_type: PhantomData,
     }
 }
 
-fn do_instantiate(mut deps: DepsMut) {
+fn public_key_of(seed: [u8; 32]) -> String {
+    let signing_key = SigningKey::from(seed);
+    hex::encode(VerificationKey::from(&signing_key).as_ref())
+}
+
+fn signer_public_key() -> String {
+    public_key_of(SIGNER_SEED)
+}
+
+fn sign_voucher(
+    env: &Env,
+    minter: &str,
+    token_uri: &str,
+    nonce: u64,
+    expires: Expiration,
+) -> String {
+    sign_voucher_with(SIGNER_SEED, env, minter, token_uri, nonce, expires)
+}
+
+fn voucher_digest(
+    env: &Env,
+    minter: &str,
+    token_uri: &str,
+    nonce: u64,
+    expires: Expiration,
+) -> [u8; 32] {
+    let voucher = MintVoucher {
+        contract_address: env.contract.address.to_string(),
+        chain_id: env.block.chain_id.clone(),
+        minter: minter.to_string(),
+        token_uri: token_uri.to_string(),
+        nonce,
+        expires,
+    };
+    voucher.digest()
+}
+
+fn sign_voucher_with(
+    seed: [u8; 32],
+    env: &Env,
+    minter: &str,
+    token_uri: &str,
+    nonce: u64,
+    expires: Expiration,
+) -> String {
+    let digest = voucher_digest(env, minter, token_uri, nonce, expires);
+    let signature: [u8; 64] = SigningKey::from(seed).sign(&digest).into();
+    hex::encode(signature)
+}
+
+// `r || s || v` signature of an Ethereum wallet calling `signMessage(digest)`
+fn eth_sign_voucher(
+    seed: [u8; 32],
+    env: &Env,
+    minter: &str,
+    token_uri: &str,
+    nonce: u64,
+) -> String {
+    let digest = voucher_digest(env, minter, token_uri, nonce, Expiration::Never {});
+    let personal_message = Keccak256::new()
+        .chain_update(b"\x19Ethereum Signed Message:\n32")
+        .chain_update(digest);
+    let signing_key = k256::ecdsa::SigningKey::from_bytes(&seed).unwrap();
+    let signature: recoverable::Signature = signing_key.sign_digest(personal_message);
+    hex::encode(signature.as_ref())
+}
+
+fn eth_address_of(seed: [u8; 32]) -> String {
+    let signing_key = k256::ecdsa::SigningKey::from_bytes(&seed).unwrap();
+    let public_key = signing_key.verifying_key().to_encoded_point(false);
+    let hash = Keccak256::digest(&public_key.as_bytes()[1..]);
+    format!("0x{}", hex::encode(&hash[12..]))
+}
+
+fn secp256k1_public_key_of(seed: [u8; 32]) -> String {
+    let signing_key = k256::ecdsa::SigningKey::from_bytes(&seed).unwrap();
+    hex::encode(
+        signing_key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes(),
+    )
+}
+
+fn mint_token_msg(env: &Env, minter: &str, token_uri: &str, nonce: u64) -> Cw721ExecuteMsg {
+    mint_token_msg_with(
+        SIGNER_SEED,
+        DEFAULT_SIGNER_KEY_ID,
+        env,
+        minter,
+        token_uri,
+        nonce,
+    )
+}
+
+fn mint_token_msg_with(
+    seed: [u8; 32],
+    key_id: &str,
+    env: &Env,
+    minter: &str,
+    token_uri: &str,
+    nonce: u64,
+) -> Cw721ExecuteMsg {
+    let mint_msg = ExecuteMsg::MintToken {
+        token_uri: token_uri.to_string(),
+        nonce,
+        expires: Expiration::Never {},
+        signatures: vec![VoucherSignature {
+            key_id: key_id.to_string(),
+            signature: sign_voucher_with(seed, env, minter, token_uri, nonce, Expiration::Never {}),
+        }],
+    };
+    Cw721ExecuteMsg::Extension { msg: mint_msg }
+}
+
+fn do_instantiate(deps: DepsMut) {
+    do_instantiate_with_policy(deps, MintPolicy::default());
+}
+
+fn do_instantiate_with_policy(mut deps: DepsMut, mint_policy: MintPolicy) {
     let instantiate_msg = InstantiateMsg {
         name: "ERC721".into(),
         symbol: "ERC721".into(),
-        public_key: "6a99e543f5eb501d51995083161e3b75528d77d26d62d83cda5439b057653d78".into(),
+        public_key: signer_public_key(),
+        mint_policy,
+        minter: None,
+        token_id_namespace: 0,
+        bridge_mode: BridgeMode::default(),
     };
     let info = mock_info(SENDER, &[]);
     let env = mock_env();
@@ -79,6 +234,89 @@ fn get_nft_info(
     }
 }
 
+fn get_mint_allowance(deps: Deps, env: Env, owner: &str) -> MintAllowanceResponse {
+    let query_msg = Cw721QueryMsg::Extension {
+        msg: QueryMsg::MintAllowance {
+            owner: owner.to_string(),
+        },
+    };
+    from_binary(&query(deps, env, query_msg).unwrap()).unwrap()
+}
+
+fn add_signer(deps: DepsMut, env: Env, key_id: &str, seed: [u8; 32]) {
+    let add_msg = ExecuteMsg::AddSigner {
+        key_id: key_id.into(),
+        public_key: public_key_of(seed),
+        scheme: SignatureScheme::Ed25519,
+    };
+    let add_msg = Cw721ExecuteMsg::Extension { msg: add_msg };
+    let res = execute(deps, env, mock_info(SENDER, &[]), add_msg);
+    assert!(res.is_ok());
+}
+
+fn set_signer_threshold(deps: DepsMut, env: Env, threshold: u32) -> Result<(), ContractError> {
+    let threshold_msg = ExecuteMsg::SetSignerThreshold { threshold };
+    let threshold_msg = Cw721ExecuteMsg::Extension { msg: threshold_msg };
+    execute(deps, env, mock_info(SENDER, &[]), threshold_msg).map(|_| ())
+}
+
+fn list_signers(deps: Deps, env: Env) -> SignersResponse {
+    let query_msg = Cw721QueryMsg::Extension {
+        msg: QueryMsg::ListSigners {
+            start_after: None,
+            limit: None,
+        },
+    };
+    from_binary(&query(deps, env, query_msg).unwrap()).unwrap()
+}
+
+fn transfer_crosschain_msg(dst_chain_id: &str, token_id: u64) -> Cw721ExecuteMsg {
+    Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::TransferCrossChain {
+            dst_chain_id: dst_chain_id.into(),
+            token_id,
+            recipient: "0x1C609537a32630c054202e2B089B9Da268667C5D".to_string(),
+            request_metadata: transfer_request_metadata(),
+        },
+    }
+}
+
+fn do_sudo(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    env: Env,
+    msg: SudoMsg,
+) -> Result<Response<RouterMsg>, ContractError> {
+    with_router_deps(deps, |deps| sudo(deps, env, msg))
+}
+
+// `HandleIReceive` of a token sent to `recipient` by the contract enrolled for `src_chain_id`
+fn receive_msg(
+    src_chain_id: &str,
+    request_sender: &str,
+    request_identifier: u64,
+    nft_id: u64,
+    recipient: &str,
+) -> SudoMsg {
+    let transfer_params = Token::Tuple(vec![
+        Token::Uint(nft_id.into()),
+        Token::Bytes(recipient.as_bytes().to_vec()),
+        Token::String(TOKEN_URI.into()),
+    ]);
+    testing::receive_msg(
+        src_chain_id,
+        request_sender,
+        request_identifier,
+        encode(&[transfer_params]),
+    )
+}
+
+fn get_transfer(deps: Deps, env: Env, request_identifier: u64) -> TransferRecord {
+    let query_msg = Cw721QueryMsg::Extension {
+        msg: QueryMsg::GetTransfer { request_identifier },
+    };
+    from_binary(&query(deps, env, query_msg).unwrap()).unwrap()
+}
+
 #[test]
 fn test_basic() {
     let mut deps = get_mock_dependencies();
@@ -86,174 +324,1198 @@ fn test_basic() {
 }
 
 #[test]
-fn test_enroll_and_get_remote_contract() {
+fn test_migrate_from_1_0_0() {
     let mut deps = mock_dependencies();
     let env = mock_env();
-    let info = mock_info(SENDER, &[]);
-    let chain_id = "cosmos".to_string();
-    let remote_contract = "wasm1kjd9yyyqx0jwfzzy9ls32vuuyfem38x2lg2y0g".to_string();
 
-    do_instantiate(deps.as_mut());
+    // what a 1.0.0 deployment left in storage
+    cw2::set_contract_version(&mut deps.storage, "xerc721", "1.0.0").unwrap();
+    OWNER.save(&mut deps.storage, &SENDER.to_string()).unwrap();
+    TOTAL_SUPPLY.save(&mut deps.storage, &1).unwrap();
+    LEGACY_PUBLIC_KEY
+        .save(&mut deps.storage, &signer_public_key())
+        .unwrap();
+    LEGACY_ALREADY_MINTED
+        .save(&mut deps.storage, SENDER.to_string(), &true)
+        .unwrap();
 
-    set_remote_contract(
+    // 1.0.0 never saved the cw721 collection info
+    let migrate_msg = MigrateMsg {
+        name: Some("Riders".into()),
+        symbol: None,
+        minter: None,
+    };
+    let res = with_router_deps(&mut deps, |deps| {
+        migrate(deps, env.clone(), migrate_msg.clone())
+    });
+    assert_eq!(res.unwrap_err(), ContractError::MissingCollectionInfo {});
+    let migrate_msg = MigrateMsg {
+        symbol: Some("RDR".into()),
+        ..migrate_msg
+    };
+    with_router_deps(&mut deps, |deps| migrate(deps, env.clone(), migrate_msg)).unwrap();
+    assert!(LEGACY_PUBLIC_KEY.may_load(&deps.storage).unwrap().is_none());
+
+    // addresses that minted before the upgrade keep their one mint
+    let allowance = get_mint_allowance(deps.as_ref(), env.clone(), SENDER);
+    assert_eq!(allowance.minted, 1);
+    assert_eq!(allowance.remaining, Some(0));
+    let res = execute(
         deps.as_mut(),
         env.clone(),
-        info,
-        chain_id.clone(),
-        remote_contract.clone(),
+        mock_info(SENDER, &[]),
+        mint_token_msg(&env, SENDER, TOKEN_URI, 0),
     );
-    // Get remote contract
-    let extension_msg = QueryMsg::GetRemoteContract { chain_id };
-    let query_msg = Cw721QueryMsg::Extension { msg: extension_msg };
-    let res = query(deps.as_ref(), env.clone(), query_msg.clone());
-    let remote_contract_result: String = from_binary(&res.unwrap()).unwrap();
+    assert_eq!(res.unwrap_err(), ContractError::MintLimitReached {});
 
-    // Check if remote contract is set
-    assert_eq!(remote_contract_result, remote_contract);
+    // the old public key signs for the `default` signer
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        mint_token_msg(&env, OTHER_SENDER, TOKEN_URI, 0),
+    );
+    assert!(res.is_ok());
+    let signers = list_signers(deps.as_ref(), env.clone());
+    assert_eq!(signers.signers.len(), 1);
+
+    // the cw721 entry points work on the migrated collection, the owner is its minter
+    let minter: MinterResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), Cw721QueryMsg::Minter {}).unwrap()).unwrap();
+    assert_eq!(minter.minter, SENDER);
+    let admin_mint_msg = Cw721ExecuteMsg::Mint(cw721_base::MintMsg {
+        token_id: "100".into(),
+        owner: OTHER_SENDER.into(),
+        token_uri: None,
+        extension: Empty {},
+    });
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(SENDER, &[]),
+        admin_mint_msg,
+    );
+    assert!(res.is_ok());
+    let update_msg = Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::UpdateCollectionInfo {
+            name: None,
+            symbol: Some("RIDE".into()),
+        },
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(SENDER, &[]),
+        update_msg,
+    );
+    assert!(res.is_ok());
+    let contract_info: ContractInfoResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), Cw721QueryMsg::ContractInfo {}).unwrap())
+            .unwrap();
+    assert_eq!(contract_info.name, "Riders");
+    assert_eq!(contract_info.symbol, "RIDE");
+
+    let query_msg = Cw721QueryMsg::Extension {
+        msg: QueryMsg::GetContractVersion {},
+    };
+    let version: cw2::ContractVersion =
+        from_binary(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+    assert_eq!(version.version, "1.1.0");
 }
 
 #[test]
-fn test_mint_nft() {
+fn test_instantiate_rejects_malformed_public_key() {
     let mut deps = mock_dependencies();
-    let env = mock_env();
+    let instantiate = |deps: DepsMut, public_key: &str| {
+        let instantiate_msg = InstantiateMsg {
+            name: "ERC721".into(),
+            symbol: "ERC721".into(),
+            public_key: public_key.into(),
+            mint_policy: MintPolicy::default(),
+            minter: None,
+            token_id_namespace: 0,
+            bridge_mode: BridgeMode::default(),
+        };
+        instantiate(deps, mock_env(), mock_info(SENDER, &[]), instantiate_msg)
+    };
+
+    let err = instantiate(deps.as_mut(), "not hex").unwrap_err();
+    assert!(err.to_string().contains("Malformed public key"));
+
+    let err = instantiate(deps.as_mut(), "6a99e5").unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Signature(SignatureError::InvalidPublicKeyLength {
+            expected: "32".to_string(),
+            actual: 3,
+        })
+    );
+
+    assert!(instantiate(deps.as_mut(), &signer_public_key()).is_ok());
+}
+
+#[test]
+fn test_two_step_ownership_transfer() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
     let info = mock_info(SENDER, &[]);
+    let extension = |msg: ExecuteMsg| Cw721ExecuteMsg::Extension { msg };
+    let get_owner = |deps: Deps, env: Env| -> Option<String> {
+        let query_msg = Cw721QueryMsg::Extension {
+            msg: QueryMsg::GetOwner {},
+        };
+        from_binary(&query(deps, env, query_msg).unwrap()).unwrap()
+    };
+    let get_pending_owner = |deps: Deps, env: Env| -> PendingOwnerResponse {
+        let query_msg = Cw721QueryMsg::Extension {
+            msg: QueryMsg::GetPendingOwner {},
+        };
+        from_binary(&query(deps, env, query_msg).unwrap()).unwrap()
+    };
 
     do_instantiate(deps.as_mut());
+    let expires = Expiration::AtHeight(env.block.height + 10);
+    let propose_msg = extension(ExecuteMsg::ProposeNewOwner {
+        new_owner: OTHER_SENDER.into(),
+        expires,
+    });
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        propose_msg.clone(),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::InvalidOwner {});
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        propose_msg.clone(),
+    );
+    assert!(res.is_ok());
+    assert_eq!(
+        get_pending_owner(deps.as_ref(), env.clone()),
+        PendingOwnerResponse {
+            pending_owner: Some(OTHER_SENDER.into()),
+            expires: Some(expires),
+        }
+    );
 
-    let mint_msg = ExecuteMsg::MintToken { 
-        token_uri: "https://ipfs.io/ipfs/Qma49KCamwSberpE8whTz3ESk81PoGtLsimmj3YhsWJKPX".to_string(), 
-        signature: "4676479df870e8fdb75b3b24fbe1ca7748bce5bfa9a6621087603d3ac97c1b5a00da3768adaefbf1ecd88d6ab576bb42c1c7325666349ecf7487385286757a09".to_string(), 
-    };
-    let mint_msg = Cw721ExecuteMsg::Extension{ msg: mint_msg };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
+    // the owner keeps control until the proposal is accepted
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        extension(ExecuteMsg::AcceptOwnership {}),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::NotPendingOwner {});
+    assert_eq!(get_owner(deps.as_ref(), env.clone()), Some(SENDER.into()));
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        extension(ExecuteMsg::CancelOwnershipTransfer {}),
+    );
+    assert!(res.is_ok());
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        extension(ExecuteMsg::AcceptOwnership {}),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::NoPendingOwner {});
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), propose_msg);
+    assert!(res.is_ok());
+    env.block.height += 10;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        extension(ExecuteMsg::AcceptOwnership {}),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::OwnershipProposalExpired {});
+
+    let propose_msg = extension(ExecuteMsg::ProposeNewOwner {
+        new_owner: OTHER_SENDER.into(),
+        expires: Expiration::Never {},
+    });
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), propose_msg);
+    assert!(res.is_ok());
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        extension(ExecuteMsg::AcceptOwnership {}),
+    );
+    assert!(res.is_ok());
+    assert_eq!(
+        get_owner(deps.as_ref(), env.clone()),
+        Some(OTHER_SENDER.into())
+    );
+    assert_eq!(
+        get_pending_owner(deps.as_ref(), env.clone()).pending_owner,
+        None
+    );
+
+    // the previous owner is locked out, the new one can renounce
+    let renounce_msg = extension(ExecuteMsg::RenounceOwnership {});
+    let res = execute(deps.as_mut(), env.clone(), info, renounce_msg.clone());
+    assert_eq!(res.unwrap_err(), ContractError::InvalidOwner {});
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        renounce_msg.clone(),
+    );
     assert!(res.is_ok());
+    assert_eq!(get_owner(deps.as_ref(), env.clone()), None);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(OTHER_SENDER, &[]),
+        renounce_msg,
+    );
+    assert_eq!(res.unwrap_err(), ContractError::InvalidOwner {});
 }
 
 #[test]
-fn test_can_not_mint_nft_with_wrong_sign() {
+fn test_roles_and_pause() {
     let mut deps = mock_dependencies();
     let env = mock_env();
     let info = mock_info(SENDER, &[]);
+    let operator = mock_info(OTHER_SENDER, &[]);
+    let extension = |msg: ExecuteMsg| Cw721ExecuteMsg::Extension { msg };
 
     do_instantiate(deps.as_mut());
+    let pause_msg = extension(ExecuteMsg::Pause {});
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        operator.clone(),
+        pause_msg.clone(),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MissingRole { role: Role::Pauser }
+    );
 
-    let mint_msg = ExecuteMsg::MintToken { 
-        token_uri: "https://ipfs.io/ipfs/Qma49KCamwSberpE8whTz3ESk81PoGtLsimmj3YhsWJKPX".to_string(), 
-        signature: "4676479df870e8fdb75b3b24fbe1ca7748bce5bfa9a6621087603d3ac97c1b5a00da3768adaefbf1ecd88d6ab576bb42c1c7325666349ecf7487385286757a01".to_string(), 
+    let grant_msg = extension(ExecuteMsg::GrantRole {
+        role: Role::Pauser,
+        address: OTHER_SENDER.into(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        operator.clone(),
+        grant_msg.clone(),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MissingRole { role: Role::Admin }
+    );
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), grant_msg);
+    assert!(res.is_ok());
+    let query_msg = Cw721QueryMsg::Extension {
+        msg: QueryMsg::RolesOf {
+            address: OTHER_SENDER.into(),
+        },
     };
-    let mint_msg = Cw721ExecuteMsg::Extension{ msg: mint_msg };
+    let roles: RolesResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+    assert_eq!(roles.roles, vec![Role::Pauser]);
+
+    // a pauser can not manage the bridge
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        operator.clone(),
+        extension(ExecuteMsg::EnrollRemoteContract {
+            chain_id: "80001".into(),
+            remote_address: "0xc27CE28850774288B3EF678c4550161346944152".into(),
+        }),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MissingRole {
+            role: Role::BridgeManager
+        }
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), operator.clone(), pause_msg);
+    assert!(res.is_ok());
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
     let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
-    assert!(res.is_err());
+    assert_eq!(res.unwrap_err(), ContractError::Paused {});
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        operator.clone(),
+        extension(ExecuteMsg::Unpause {}),
+    );
+    assert!(res.is_ok());
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert!(res.is_ok());
+
+    let revoke_msg = extension(ExecuteMsg::RevokeRole {
+        role: Role::Pauser,
+        address: OTHER_SENDER.into(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), info, revoke_msg);
+    assert!(res.is_ok());
+    let res = execute(
+        deps.as_mut(),
+        env,
+        operator,
+        extension(ExecuteMsg::Pause {}),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MissingRole { role: Role::Pauser }
+    );
 }
 
 #[test]
-fn test_can_not_mint_nft_twice() {
+fn test_minter_role_can_admin_mint() {
     let mut deps = mock_dependencies();
     let env = mock_env();
     let info = mock_info(SENDER, &[]);
+    let mint_msg = |token_id: &str| {
+        Cw721ExecuteMsg::Mint(cw721_base::MintMsg {
+            token_id: token_id.into(),
+            owner: OTHER_SENDER.into(),
+            token_uri: Some(TOKEN_URI.into()),
+            extension: Empty {},
+        })
+    };
 
     do_instantiate(deps.as_mut());
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        mint_msg("100"),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MissingRole { role: Role::Minter }
+    );
 
-    let mint_msg = ExecuteMsg::MintToken { 
-        token_uri: "https://ipfs.io/ipfs/Qma49KCamwSberpE8whTz3ESk81PoGtLsimmj3YhsWJKPX".to_string(), 
-        signature: "4676479df870e8fdb75b3b24fbe1ca7748bce5bfa9a6621087603d3ac97c1b5a00da3768adaefbf1ecd88d6ab576bb42c1c7325666349ecf7487385286757a09".to_string(), 
+    let grant_msg = Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::GrantRole {
+            role: Role::Minter,
+            address: OTHER_SENDER.into(),
+        },
     };
-    let mint_msg = Cw721ExecuteMsg::Extension{ msg: mint_msg };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
+    let res = execute(deps.as_mut(), env.clone(), info, grant_msg);
+    assert!(res.is_ok());
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        mint_msg("100"),
+    );
     assert!(res.is_ok());
+    let owner_of = get_nft_owner_of(deps.as_ref(), env.clone(), "100".into()).unwrap();
+    assert_eq!(owner_of.owner, OTHER_SENDER);
 
-    let mint_msg = ExecuteMsg::MintToken { 
-        token_uri: "https://ipfs.io/ipfs/QmakZtb3uKouz5QFGTaUvjN3akJDkAgGGSLh61aqEUnGt1".to_string(), 
-        signature: "c2623cdaf5e714b7f7e64ae0ebb49b5ddeb199215016395a75ac6a81985a1dc97d2fe6f79ca4e5f7305ce49d9ad8c11a4ce2db1d499fdcc2f841fae20bf4b90c".to_string(), 
-    };
-    let mint_msg = Cw721ExecuteMsg::Extension{ msg: mint_msg };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
-    assert!(res.is_err());
+    // ids outside namespace 0 belong to other deployments
+    let foreign_id = namespaced_token_id(7, 100);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(SENDER, &[]),
+        mint_msg(&foreign_id.to_string()),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::ForeignTokenId {
+            token_id: foreign_id,
+            namespace: 0
+        }
+    );
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(SENDER, &[]),
+        mint_msg("rider"),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InvalidTokenId {
+            token_id: "rider".into()
+        }
+    );
 }
 
 #[test]
-fn test_two_mint_nft() {
+fn test_collection_info_and_minter() {
     let mut deps = mock_dependencies();
     let env = mock_env();
     let info = mock_info(SENDER, &[]);
 
     do_instantiate(deps.as_mut());
+    let contract_info: ContractInfoResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), Cw721QueryMsg::ContractInfo {}).unwrap())
+            .unwrap();
+    assert_eq!(contract_info.name, "ERC721");
+    assert_eq!(contract_info.symbol, "ERC721");
+    let minter: MinterResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), Cw721QueryMsg::Minter {}).unwrap()).unwrap();
+    assert_eq!(minter.minter, SENDER);
 
-    let mint_msg = ExecuteMsg::MintToken { 
-        token_uri: "https://ipfs.io/ipfs/Qma49KCamwSberpE8whTz3ESk81PoGtLsimmj3YhsWJKPX".to_string(), 
-        signature: "4676479df870e8fdb75b3b24fbe1ca7748bce5bfa9a6621087603d3ac97c1b5a00da3768adaefbf1ecd88d6ab576bb42c1c7325666349ecf7487385286757a09".to_string(), 
+    let update_msg = Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::UpdateCollectionInfo {
+            name: Some("Riders".into()),
+            symbol: None,
+        },
     };
-    let mint_msg = Cw721ExecuteMsg::Extension{ msg: mint_msg };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        update_msg.clone(),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MissingRole { role: Role::Admin }
+    );
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), update_msg);
     assert!(res.is_ok());
 
-    let response = get_nft_info(deps.as_ref(), env.clone(), "0".into());
-    assert!(response.is_ok());
-
-    let mint_msg = ExecuteMsg::MintToken { 
-        token_uri: "https://ipfs.io/ipfs/QmakZtb3uKouz5QFGTaUvjN3akJDkAgGGSLh61aqEUnGt1".to_string(), 
-        signature: "c2623cdaf5e714b7f7e64ae0ebb49b5ddeb199215016395a75ac6a81985a1dc97d2fe6f79ca4e5f7305ce49d9ad8c11a4ce2db1d499fdcc2f841fae20bf4b90c".to_string(), 
+    let update_msg = Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::UpdateMinter {
+            minter: OTHER_SENDER.into(),
+        },
     };
-    let mint_msg = Cw721ExecuteMsg::Extension{ msg: mint_msg };
-    let res = execute(deps.as_mut(), env.clone(), mock_info("router1apapk9zfz3rp4x87fsm6h0s3zd0wlmkz0fx8tx", &[]), mint_msg.clone());
+    let res = execute(deps.as_mut(), env.clone(), info, update_msg);
     assert!(res.is_ok());
 
-    let response = get_nft_info(deps.as_ref(), env.clone(),"1".into());
-    assert!(response.is_ok());
+    let contract_info: ContractInfoResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), Cw721QueryMsg::ContractInfo {}).unwrap())
+            .unwrap();
+    assert_eq!(contract_info.name, "Riders");
+    assert_eq!(contract_info.symbol, "ERC721");
+    let minter: MinterResponse =
+        from_binary(&query(deps.as_ref(), env, Cw721QueryMsg::Minter {}).unwrap()).unwrap();
+    assert_eq!(minter.minter, OTHER_SENDER);
 }
 
 #[test]
-fn test_transfer_crosschain() {
+fn test_enroll_and_get_remote_contract() {
     let mut deps = mock_dependencies();
     let env = mock_env();
     let info = mock_info(SENDER, &[]);
+    let chain_id = "cosmos".to_string();
     let remote_contract = "wasm1kjd9yyyqx0jwfzzy9ls32vuuyfem38x2lg2y0g".to_string();
 
     do_instantiate(deps.as_mut());
+
     set_remote_contract(
         deps.as_mut(),
         env.clone(),
-        info.clone(),
-        "1".into(),
-        remote_contract,
+        info,
+        chain_id.clone(),
+        remote_contract.clone(),
     );
+    // Get remote contract
+    let extension_msg = QueryMsg::GetRemoteContract { chain_id };
+    let query_msg = Cw721QueryMsg::Extension { msg: extension_msg };
+    let res = query(deps.as_ref(), env.clone(), query_msg.clone());
+    let remote_contract_result: String = from_binary(&res.unwrap()).unwrap();
 
-    let mint_msg = ExecuteMsg::MintToken { 
-        token_uri: "https://ipfs.io/ipfs/Qma49KCamwSberpE8whTz3ESk81PoGtLsimmj3YhsWJKPX".to_string(), 
-        signature: "4676479df870e8fdb75b3b24fbe1ca7748bce5bfa9a6621087603d3ac97c1b5a00da3768adaefbf1ecd88d6ab576bb42c1c7325666349ecf7487385286757a09".to_string(),
-    };
-    let mint_msg = Cw721ExecuteMsg::Extension{ msg: mint_msg };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
-    assert!(res.is_ok());
+    // Check if remote contract is set
+    assert_eq!(remote_contract_result, remote_contract);
+}
 
-    let request_metadata: RequestMetaData = RequestMetaData {
-        dest_gas_limit: 0,
-        ack_gas_limit: 0,
-        dest_gas_price: 0,
-        ack_gas_price: 0,
-        relayer_fee: Uint128::from(0u32),
-        ack_type: router_wasm_bindings::types::AckType::AckOnBoth,
-        is_read_call: false,
-        asm_address: "".into(),
-    };
-    let response = get_nft_info(deps.as_ref(), env.clone(), "0".into());
-    assert!(response.is_ok());
+#[test]
+fn test_mint_nft() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
 
-    let respone = get_nft_owner_of(deps.as_ref(), env.clone(), "0".into());
-    assert!(response.is_ok());
-    assert_eq!(respone.unwrap().owner, SENDER);
+    do_instantiate(deps.as_mut());
 
-    let ext_cc_msg = ExecuteMsg::TransferCrossChain {
-        dst_chain_id: "1".into(),
-        token_id: 0,
-        recipient: "0x1C609537a32630c054202e2B089B9Da268667C5D".to_string(),
-        request_metadata,
-    };
-    let exec_msg = Cw721ExecuteMsg::Extension { msg: ext_cc_msg };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), exec_msg.clone());
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
     assert!(res.is_ok());
+}
 
-    if let Ok(result) = res {
+#[test]
+fn test_can_not_mint_nft_with_wrong_sign() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+
+    let mut signature = hex::decode(sign_voucher(
+        &env,
+        SENDER,
+        TOKEN_URI,
+        0,
+        Expiration::Never {},
+    ))
+    .unwrap();
+    signature[63] ^= 1;
+    let mint_msg = ExecuteMsg::MintToken {
+        token_uri: TOKEN_URI.to_string(),
+        nonce: 0,
+        expires: Expiration::Never {},
+        signatures: vec![VoucherSignature {
+            key_id: DEFAULT_SIGNER_KEY_ID.to_string(),
+            signature: hex::encode(signature),
+        }],
+    };
+    let mint_msg = Cw721ExecuteMsg::Extension { msg: mint_msg };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_can_not_mint_nft_with_malformed_signature() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+
+    let mint_msg = |signature: String| Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::MintToken {
+            token_uri: TOKEN_URI.to_string(),
+            nonce: 0,
+            expires: Expiration::Never {},
+            signatures: vec![VoucherSignature {
+                key_id: DEFAULT_SIGNER_KEY_ID.to_string(),
+                signature,
+            }],
+        },
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        mint_msg("zz".into()),
+    );
+    assert!(res.unwrap_err().to_string().contains("Malformed signature"));
+
+    let signature = sign_voucher(&env, SENDER, TOKEN_URI, 0, Expiration::Never {});
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        mint_msg(signature[..126].to_string()),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::Signature(SignatureError::InvalidSignatureLength {
+            expected: "64".to_string(),
+            actual: 63,
+        })
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        mint_msg(signature),
+    );
+    assert!(res.is_ok());
+}
+
+#[test]
+fn test_can_not_add_signer_with_wrong_key_length() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+
+    let add_msg = ExecuteMsg::AddSigner {
+        key_id: "evm-backend".into(),
+        public_key: "0x1C609537a32630c054202e2B089B9Da268".into(),
+        scheme: SignatureScheme::Secp256k1,
+    };
+    let add_msg = Cw721ExecuteMsg::Extension { msg: add_msg };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), add_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::Signature(SignatureError::InvalidPublicKeyLength {
+            expected: "20 (address), 33 or 65".to_string(),
+            actual: 17,
+        })
+    );
+}
+
+#[test]
+fn test_can_not_mint_nft_with_voucher_of_other_minter() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    do_instantiate(deps.as_mut());
+
+    // voucher issued to SENDER, submitted by OTHER_SENDER
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        mint_msg,
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::Signature(SignatureError::VerificationFailed {})
+    );
+}
+
+#[test]
+fn test_can_not_mint_nft_with_voucher_of_other_deployment() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+
+    let mut other_env = mock_env();
+    other_env.block.chain_id = "other-chain".to_string();
+    let mint_msg = mint_token_msg(&other_env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert!(res.is_err());
+
+    let mut other_env = mock_env();
+    other_env.contract.address = Addr::unchecked("other_contract");
+    let mint_msg = mint_token_msg(&other_env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_can_not_mint_nft_with_expired_voucher() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+
+    let expires = Expiration::AtHeight(env.block.height);
+    let mint_msg = ExecuteMsg::MintToken {
+        token_uri: TOKEN_URI.to_string(),
+        nonce: 0,
+        expires,
+        signatures: vec![VoucherSignature {
+            key_id: DEFAULT_SIGNER_KEY_ID.to_string(),
+            signature: sign_voucher(&env, SENDER, TOKEN_URI, 0, expires),
+        }],
+    };
+    let mint_msg = Cw721ExecuteMsg::Extension { msg: mint_msg };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert_eq!(res.unwrap_err(), ContractError::VoucherExpired {});
+
+    let expires = Expiration::AtTime(env.block.time.plus_seconds(60));
+    let mint_msg = ExecuteMsg::MintToken {
+        token_uri: TOKEN_URI.to_string(),
+        nonce: 0,
+        expires,
+        signatures: vec![VoucherSignature {
+            key_id: DEFAULT_SIGNER_KEY_ID.to_string(),
+            signature: sign_voucher(&env, SENDER, TOKEN_URI, 0, expires),
+        }],
+    };
+    let mint_msg = Cw721ExecuteMsg::Extension { msg: mint_msg };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert!(res.is_ok());
+}
+
+#[test]
+fn test_can_not_mint_nft_twice() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
+    assert!(res.is_ok());
+
+    let mint_msg = mint_token_msg(&env, SENDER, OTHER_TOKEN_URI, 1);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_unlimited_mint_policy() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate_with_policy(deps.as_mut(), MintPolicy::Unlimited {});
+
+    for nonce in 0..3 {
+        let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, nonce);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+        assert!(res.is_ok());
+    }
+
+    let allowance = get_mint_allowance(deps.as_ref(), env.clone(), SENDER);
+    assert_eq!(
+        allowance,
+        MintAllowanceResponse {
+            minted: 3,
+            remaining: None
+        }
+    );
+}
+
+#[test]
+fn test_per_address_mint_policy() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    let instantiate_msg = InstantiateMsg {
+        name: "ERC721".into(),
+        symbol: "ERC721".into(),
+        public_key: signer_public_key(),
+        mint_policy: MintPolicy::PerAddress { limit: 0 },
+        minter: None,
+        token_id_namespace: 0,
+        bridge_mode: BridgeMode::default(),
+    };
+    let res = instantiate(deps.as_mut(), env.clone(), info.clone(), instantiate_msg);
+    assert_eq!(res.unwrap_err(), ContractError::InvalidMintPolicy {});
+
+    let mut deps = mock_dependencies();
+    do_instantiate_with_policy(deps.as_mut(), MintPolicy::PerAddress { limit: 2 });
+
+    let allowance = get_mint_allowance(deps.as_ref(), env.clone(), SENDER);
+    assert_eq!(allowance.remaining, Some(2));
+
+    for nonce in 0..2 {
+        let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, nonce);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+        assert!(res.is_ok());
+    }
+
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 2);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert_eq!(res.unwrap_err(), ContractError::MintLimitReached {});
+
+    let allowance = get_mint_allowance(deps.as_ref(), env.clone(), SENDER);
+    assert_eq!(
+        allowance,
+        MintAllowanceResponse {
+            minted: 2,
+            remaining: Some(0)
+        }
+    );
+}
+
+#[test]
+fn test_can_not_replay_voucher() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate_with_policy(deps.as_mut(), MintPolicy::Unlimited {});
+
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
+    assert!(res.is_ok());
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
+    assert_eq!(res.unwrap_err(), ContractError::VoucherAlreadyConsumed {});
+
+    // nor with the recovery byte appended to the signature
+    let mut mint_msg = mint_msg;
+    if let Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::MintToken { signatures, .. },
+    } = &mut mint_msg
+    {
+        signatures[0].signature.push_str("1b");
+    }
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert_eq!(res.unwrap_err(), ContractError::VoucherAlreadyConsumed {});
+
+    // a different voucher cannot reuse the nonce either
+    let mint_msg = mint_token_msg(&env, SENDER, OTHER_TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::NonceAlreadyUsed { nonce: 0 }
+    );
+}
+
+#[test]
+fn test_add_signer() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+
+    let add_msg = ExecuteMsg::AddSigner {
+        key_id: "backend-2".into(),
+        public_key: public_key_of(OTHER_SIGNER_SEED),
+        scheme: SignatureScheme::Ed25519,
+    };
+    let add_msg = Cw721ExecuteMsg::Extension { msg: add_msg };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        add_msg.clone(),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MissingRole {
+            role: Role::SignerManager
+        }
+    );
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), add_msg.clone());
+    assert!(res.is_ok());
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), add_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::SignerAlreadyExists {
+            key_id: "backend-2".to_string()
+        }
+    );
+
+    let signers = list_signers(deps.as_ref(), env.clone()).signers;
+    assert_eq!(signers.len(), 2);
+    assert_eq!(signers[0].key_id, "backend-2");
+    assert_eq!(signers[0].public_key, public_key_of(OTHER_SIGNER_SEED));
+    assert_eq!(signers[0].added_at, env.block.time);
+    assert!(signers.iter().all(|signer| signer.active));
+
+    // a voucher of the new key must name it
+    let mint_msg = mint_token_msg_with(
+        OTHER_SIGNER_SEED,
+        DEFAULT_SIGNER_KEY_ID,
+        &env,
+        SENDER,
+        TOKEN_URI,
+        0,
+    );
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::Signature(SignatureError::VerificationFailed {})
+    );
+    let mint_msg = mint_token_msg_with(OTHER_SIGNER_SEED, "backend-2", &env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert!(res.is_ok());
+}
+
+#[test]
+fn test_revoke_signer() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+    add_signer(deps.as_mut(), env.clone(), "backend-2", OTHER_SIGNER_SEED);
+
+    let revoke_msg = ExecuteMsg::RevokeSigner {
+        key_id: DEFAULT_SIGNER_KEY_ID.into(),
+    };
+    let revoke_msg = Cw721ExecuteMsg::Extension { msg: revoke_msg };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), revoke_msg.clone());
+    assert!(res.is_ok());
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), revoke_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::SignerAlreadyRevoked {
+            key_id: DEFAULT_SIGNER_KEY_ID.to_string()
+        }
+    );
+
+    let signers = list_signers(deps.as_ref(), env.clone()).signers;
+    assert_eq!(signers.len(), 2);
+    assert_eq!(signers[1].key_id, DEFAULT_SIGNER_KEY_ID);
+    assert!(!signers[1].active);
+    assert_eq!(signers[1].revoked_at, Some(env.block.time));
+
+    // the last active signer cannot go while the threshold needs it
+    let revoke_msg = ExecuteMsg::RevokeSigner {
+        key_id: "backend-2".into(),
+    };
+    let revoke_msg = Cw721ExecuteMsg::Extension { msg: revoke_msg };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), revoke_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::SignersBelowThreshold {
+            active: 0,
+            threshold: 1
+        }
+    );
+
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::UnknownSigner {
+            key_id: DEFAULT_SIGNER_KEY_ID.to_string()
+        }
+    );
+}
+
+#[test]
+fn test_rotate_signer() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate_with_policy(deps.as_mut(), MintPolicy::Unlimited {});
+
+    let rotate_msg = ExecuteMsg::RotateSigner {
+        key_id: DEFAULT_SIGNER_KEY_ID.into(),
+        new_key_id: "backend-2".into(),
+        public_key: public_key_of(OTHER_SIGNER_SEED),
+        scheme: SignatureScheme::Ed25519,
+    };
+    let rotate_msg = Cw721ExecuteMsg::Extension { msg: rotate_msg };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), rotate_msg);
+    assert!(res.is_ok());
+
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert!(res.is_err());
+
+    let mint_msg = mint_token_msg_with(OTHER_SIGNER_SEED, "backend-2", &env, SENDER, TOKEN_URI, 1);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert!(res.is_ok());
+
+    let signers = list_signers(deps.as_ref(), env.clone()).signers;
+    let active: Vec<&str> = signers
+        .iter()
+        .filter(|signer| signer.active)
+        .map(|signer| signer.key_id.as_str())
+        .collect();
+    assert_eq!(active, vec!["backend-2"]);
+}
+
+#[test]
+fn test_threshold_mint() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+    add_signer(deps.as_mut(), env.clone(), "backend-2", OTHER_SIGNER_SEED);
+
+    assert_eq!(
+        set_signer_threshold(deps.as_mut(), env.clone(), 3).unwrap_err(),
+        ContractError::InvalidThreshold {
+            threshold: 3,
+            active: 2
+        }
+    );
+    assert!(set_signer_threshold(deps.as_mut(), env.clone(), 0).is_err());
+    assert!(set_signer_threshold(deps.as_mut(), env.clone(), 2).is_ok());
+
+    let query_msg = Cw721QueryMsg::Extension {
+        msg: QueryMsg::GetSignerThreshold {},
+    };
+    let threshold: u32 =
+        from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+    assert_eq!(threshold, 2);
+
+    let signature = |seed, key_id: &str| VoucherSignature {
+        key_id: key_id.to_string(),
+        signature: sign_voucher_with(seed, &env, SENDER, TOKEN_URI, 0, Expiration::Never {}),
+    };
+    let mint_msg = |signatures| Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::MintToken {
+            token_uri: TOKEN_URI.to_string(),
+            nonce: 0,
+            expires: Expiration::Never {},
+            signatures,
+        },
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        mint_msg(vec![signature(SIGNER_SEED, DEFAULT_SIGNER_KEY_ID)]),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::NotEnoughSignatures {
+            got: 1,
+            required: 2
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        mint_msg(vec![
+            signature(SIGNER_SEED, DEFAULT_SIGNER_KEY_ID),
+            signature(SIGNER_SEED, DEFAULT_SIGNER_KEY_ID),
+        ]),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::DuplicateSigner {
+            key_id: DEFAULT_SIGNER_KEY_ID.to_string()
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        mint_msg(vec![
+            signature(SIGNER_SEED, DEFAULT_SIGNER_KEY_ID),
+            signature(OTHER_SIGNER_SEED, "backend-2"),
+        ]),
+    );
+    assert!(res.is_ok());
+}
+
+#[test]
+fn test_mint_with_ethereum_signer() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate_with_policy(deps.as_mut(), MintPolicy::Unlimited {});
+
+    // registered by Ethereum address, verified through public key recovery
+    let add_msg = ExecuteMsg::AddSigner {
+        key_id: "evm-backend".into(),
+        public_key: eth_address_of(ETH_SIGNER_SEED),
+        scheme: SignatureScheme::Secp256k1,
+    };
+    let add_msg = Cw721ExecuteMsg::Extension { msg: add_msg };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), add_msg);
+    assert!(res.is_ok());
+
+    // registered by compressed public key, verified with the `r || s` part
+    let add_msg = ExecuteMsg::AddSigner {
+        key_id: "evm-backend-2".into(),
+        public_key: secp256k1_public_key_of(ETH_SIGNER_SEED),
+        scheme: SignatureScheme::Secp256k1,
+    };
+    let add_msg = Cw721ExecuteMsg::Extension { msg: add_msg };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), add_msg);
+    assert!(res.is_ok());
+
+    for (nonce, key_id) in [(0, "evm-backend"), (1, "evm-backend-2")] {
+        let mint_msg = ExecuteMsg::MintToken {
+            token_uri: TOKEN_URI.to_string(),
+            nonce,
+            expires: Expiration::Never {},
+            signatures: vec![VoucherSignature {
+                key_id: key_id.to_string(),
+                signature: eth_sign_voucher(ETH_SIGNER_SEED, &env, SENDER, TOKEN_URI, nonce),
+            }],
+        };
+        let mint_msg = Cw721ExecuteMsg::Extension { msg: mint_msg };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+        assert!(res.is_ok());
+    }
+
+    // a wallet that is not registered
+    let mint_msg = ExecuteMsg::MintToken {
+        token_uri: TOKEN_URI.to_string(),
+        nonce: 2,
+        expires: Expiration::Never {},
+        signatures: vec![VoucherSignature {
+            key_id: "evm-backend".to_string(),
+            signature: eth_sign_voucher(OTHER_SIGNER_SEED, &env, SENDER, TOKEN_URI, 2),
+        }],
+    };
+    let mint_msg = Cw721ExecuteMsg::Extension { msg: mint_msg };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::Signature(SignatureError::VerificationFailed {})
+    );
+}
+
+#[test]
+fn test_two_mint_nft() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
+    assert!(res.is_ok());
+
+    let response = get_nft_info(deps.as_ref(), env.clone(), "0".into());
+    assert!(response.is_ok());
+
+    let mint_msg = mint_token_msg(&env, OTHER_SENDER, OTHER_TOKEN_URI, 0);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        mint_msg.clone(),
+    );
+    assert!(res.is_ok());
+
+    let response = get_nft_info(deps.as_ref(), env.clone(), "1".into());
+    assert!(response.is_ok());
+}
+
+#[test]
+fn test_transfer_crosschain() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+    let remote_contract = "wasm1kjd9yyyqx0jwfzzy9ls32vuuyfem38x2lg2y0g".to_string();
+
+    do_instantiate(deps.as_mut());
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "1".into(),
+        remote_contract,
+    );
+
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg.clone());
+    assert!(res.is_ok());
+
+    let request_metadata: RequestMetaData = RequestMetaData {
+        dest_gas_limit: 0,
+        ack_gas_limit: 0,
+        dest_gas_price: 0,
+        ack_gas_price: 0,
+        relayer_fee: Uint128::from(0u32),
+        ack_type: router_wasm_bindings::types::AckType::AckOnBoth,
+        is_read_call: false,
+        asm_address: "".into(),
+    };
+    let response = get_nft_info(deps.as_ref(), env.clone(), "0".into());
+    assert!(response.is_ok());
+
+    let respone = get_nft_owner_of(deps.as_ref(), env.clone(), "0".into());
+    assert!(response.is_ok());
+    assert_eq!(respone.unwrap().owner, SENDER);
+
+    let ext_cc_msg = ExecuteMsg::TransferCrossChain {
+        dst_chain_id: "1".into(),
+        token_id: 0,
+        recipient: "0x1C609537a32630c054202e2B089B9Da268667C5D".to_string(),
+        request_metadata,
+    };
+    let exec_msg = Cw721ExecuteMsg::Extension { msg: ext_cc_msg };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), exec_msg.clone());
+    assert!(res.is_ok());
+
+    if let Ok(result) = res {
         let _ok = match result.messages[0].msg.clone() {
             CosmosMsg::Custom(msg) => match msg {
                 RouterMsg::CrosschainCall {
@@ -267,28 +1529,1445 @@ fn test_transfer_crosschain() {
                     // in order to verify encoded payload
                     // println!("{:?}", hex::encode(request_packet));
 
-                    //Binary(hex::decode(op).unwrap())
-                    Ok(Response::<RouterMsg>::new())
-                }
+                    //Binary(hex::decode(op).unwrap())
+                    Ok(Response::<RouterMsg>::new())
+                }
+            },
+            _ => Err(StdError::NotFound {
+                kind: "isend".into(),
+            }),
+        };
+    }
+    // nft should be burned with id 0
+    let response = get_nft_info(deps.as_ref(), env.clone(), "0".into());
+    assert!(response.is_err());
+
+    // try mint after cross chain transfer
+    let mint_msg = mint_token_msg(&env, OTHER_SENDER, TOKEN_URI, 0);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        mint_msg.clone(),
+    );
+    assert!(res.is_ok());
+
+    let response = get_nft_info(deps.as_ref(), env.clone(), "1".into());
+    assert!(response.is_ok());
+}
+
+#[test]
+fn test_failed_ack_refunds_token() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "1".into(),
+        "0xc27CE28850774288B3EF678c4550161346944152".into(),
+    );
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert!(res.is_ok());
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        transfer_crosschain_msg("1", 0),
+    )
+    .unwrap();
+    assert_eq!(res.messages[0].id, CREATE_OUTBOUND_REPLY_ID);
+    assert!(get_nft_info(deps.as_ref(), env.clone(), "0".into()).is_err());
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        crosschain_call_reply(CREATE_OUTBOUND_REPLY_ID, 7),
+    );
+    assert!(res.is_ok());
+    assert_eq!(
+        get_transfer(deps.as_ref(), env.clone(), 7).tokens,
+        vec![TransferredToken {
+            token_id: 0,
+            owner: SENDER.into(),
+            token_uri: Some(TOKEN_URI.into()),
+        }]
+    );
+
+    let res = do_sudo(&mut deps, env.clone(), ack_msg(8, false));
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::UnknownRequest {
+            request_identifier: 8
+        }
+    );
+    let res = do_sudo(&mut deps, env.clone(), ack_msg(7, false));
+    assert!(res.is_ok());
+    let owner_of = get_nft_owner_of(deps.as_ref(), env.clone(), "0".into()).unwrap();
+    assert_eq!(owner_of.owner, SENDER);
+    let nft_info = get_nft_info(deps.as_ref(), env.clone(), "0".into()).unwrap();
+    assert_eq!(nft_info.token_uri, Some(TOKEN_URI.into()));
+    assert_eq!(
+        get_transfer(deps.as_ref(), env.clone(), 7).status,
+        TransferStatus::Refunded
+    );
+
+    // a replayed ack can not mint the token a second time
+    let res = do_sudo(&mut deps, env, ack_msg(7, false));
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::TransferAlreadySettled {
+            request_identifier: 7
+        }
+    );
+}
+
+#[test]
+fn test_successful_ack_finalizes_transfer() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "1".into(),
+        "0xc27CE28850774288B3EF678c4550161346944152".into(),
+    );
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert!(res.is_ok());
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        transfer_crosschain_msg("1", 0),
+    );
+    assert!(res.is_ok());
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        crosschain_call_reply(CREATE_OUTBOUND_REPLY_ID, 7),
+    );
+    assert!(res.is_ok());
+
+    let res = do_sudo(&mut deps, env.clone(), ack_msg(7, true));
+    assert!(res.is_ok());
+    assert_eq!(
+        get_transfer(deps.as_ref(), env.clone(), 7).status,
+        TransferStatus::Delivered
+    );
+    assert!(get_nft_info(deps.as_ref(), env, "0".into()).is_err());
+}
+
+#[test]
+fn test_transfer_ledger_queries() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate_with_policy(deps.as_mut(), MintPolicy::Unlimited {});
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "1".into(),
+        "0xc27CE28850774288B3EF678c4550161346944152".into(),
+    );
+    for nonce in 0..3 {
+        let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, nonce);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+        assert!(res.is_ok());
+    }
+    for (token_id, request_identifier) in [(0, 10), (1, 11), (2, 12)] {
+        env.block.height += 1;
+        let transfer_msg = transfer_crosschain_msg("1", token_id);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), transfer_msg);
+        assert!(res.is_ok());
+        let res = reply(
+            deps.as_mut(),
+            env.clone(),
+            crosschain_call_reply(CREATE_OUTBOUND_REPLY_ID, request_identifier),
+        );
+        assert!(res.is_ok());
+    }
+
+    let transfer = get_transfer(deps.as_ref(), env.clone(), 11);
+    assert_eq!(transfer.tokens[0].token_id, 1);
+    assert_eq!(transfer.sender, SENDER);
+    assert_eq!(
+        transfer.recipient,
+        "0x1C609537a32630c054202e2B089B9Da268667C5D"
+    );
+    assert_eq!(transfer.dst_chain_id, "1");
+    assert_eq!(transfer.created_at_height, env.block.height - 1);
+    assert_eq!(transfer.status, TransferStatus::Pending);
+
+    let transfers_by_sender = |deps: Deps, start_after: Option<u64>| -> Vec<u64> {
+        let query_msg = Cw721QueryMsg::Extension {
+            msg: QueryMsg::TransfersBySender {
+                sender: SENDER.into(),
+                start_after,
+                limit: Some(2),
+            },
+        };
+        let res: TransfersResponse =
+            from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res.transfers
+            .iter()
+            .map(|transfer| transfer.request_identifier)
+            .collect()
+    };
+    assert_eq!(transfers_by_sender(deps.as_ref(), None), vec![10, 11]);
+    assert_eq!(transfers_by_sender(deps.as_ref(), Some(11)), vec![12]);
+
+    let query_msg = Cw721QueryMsg::Extension {
+        msg: QueryMsg::TransfersByToken {
+            token_id: 2,
+            start_after: None,
+            limit: None,
+        },
+    };
+    let res: TransfersResponse =
+        from_binary(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+    assert_eq!(res.transfers.len(), 1);
+    assert_eq!(res.transfers[0].request_identifier, 12);
+}
+
+#[test]
+fn test_duplicate_inbound_request_is_not_executed_twice() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let remote_contract = "0xc27CE28850774288B3EF678c4550161346944152";
+
+    do_instantiate(deps.as_mut());
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(SENDER, &[]),
+        "80001".into(),
+        remote_contract.into(),
+    );
+    let is_processed = |deps: Deps, request_identifier: u64| -> bool {
+        let query_msg = Cw721QueryMsg::Extension {
+            msg: QueryMsg::IsRequestProcessed {
+                src_chain_id: "80001".into(),
+                request_identifier,
+            },
+        };
+        from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap()
+    };
+    assert!(!is_processed(deps.as_ref(), 3));
+
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        receive_msg("80001", remote_contract, 3, 42, OTHER_SENDER),
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("status", "processed")));
+    assert!(is_processed(deps.as_ref(), 3));
+    let owner_of = get_nft_owner_of(deps.as_ref(), env.clone(), "42".into()).unwrap();
+    assert_eq!(owner_of.owner, OTHER_SENDER);
+
+    // the redelivery is acked the same way but mints nothing
+    let duplicate = do_sudo(
+        &mut deps,
+        env.clone(),
+        receive_msg("80001", remote_contract, 3, 43, SENDER),
+    )
+    .unwrap();
+    assert!(duplicate
+        .attributes
+        .contains(&Attribute::new("status", "duplicate")));
+    assert_eq!(duplicate.data, res.data);
+    assert!(get_nft_info(deps.as_ref(), env.clone(), "43".into()).is_err());
+    let owner_of = get_nft_owner_of(deps.as_ref(), env, "42".into()).unwrap();
+    assert_eq!(owner_of.owner, OTHER_SENDER);
+}
+
+#[test]
+fn test_token_ids_do_not_collide_with_bridged_tokens() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+    let remote_contract = "0xc27CE28850774288B3EF678c4550161346944152";
+
+    let instantiate_msg = InstantiateMsg {
+        name: "ERC721".into(),
+        symbol: "ERC721".into(),
+        public_key: signer_public_key(),
+        mint_policy: MintPolicy::Unlimited {},
+        minter: None,
+        token_id_namespace: MAX_TOKEN_ID_NAMESPACE + 1,
+        bridge_mode: BridgeMode::BurnMint,
+    };
+    let err = instantiate(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        instantiate_msg.clone(),
+    );
+    assert_eq!(
+        err.unwrap_err(),
+        ContractError::InvalidTokenIdNamespace {
+            namespace: MAX_TOKEN_ID_NAMESPACE + 1,
+            max: MAX_TOKEN_ID_NAMESPACE,
+        }
+    );
+    let instantiate_msg = InstantiateMsg {
+        token_id_namespace: 2,
+        ..instantiate_msg
+    };
+    let res = instantiate(deps.as_mut(), env.clone(), info.clone(), instantiate_msg);
+    assert!(res.is_ok());
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "80001".into(),
+        remote_contract.into(),
+    );
+
+    let first_id = namespaced_token_id(2, 0);
+    assert_eq!(first_id, 2 << 40);
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert!(res.is_ok());
+    let owner_of = get_nft_owner_of(deps.as_ref(), env.clone(), first_id.to_string()).unwrap();
+    assert_eq!(owner_of.owner, SENDER);
+
+    // a token of the same id arriving from another chain must not replace ours
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        receive_msg("80001", remote_contract, 1, first_id, OTHER_SENDER),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::TokenIdCollision { token_id: first_id }
+    );
+    let owner_of = get_nft_owner_of(deps.as_ref(), env.clone(), first_id.to_string()).unwrap();
+    assert_eq!(owner_of.owner, SENDER);
+
+    // local mints skip ids already taken by bridged tokens
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        receive_msg("80001", remote_contract, 2, first_id + 1, OTHER_SENDER),
+    );
+    assert!(res.is_ok());
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 1);
+    let res = execute(deps.as_mut(), env.clone(), info, mint_msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("token_id", (first_id + 2).to_string())));
+    let owner_of = get_nft_owner_of(deps.as_ref(), env, (first_id + 1).to_string()).unwrap();
+    assert_eq!(owner_of.owner, OTHER_SENDER);
+}
+
+#[test]
+fn test_lock_release_bridge_mode() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+    let remote_contract = "0xc27CE28850774288B3EF678c4550161346944152";
+    let list_escrowed = |deps: Deps| -> Vec<EscrowedToken> {
+        let query_msg = Cw721QueryMsg::Extension {
+            msg: QueryMsg::ListEscrowed {
+                start_after: None,
+                limit: None,
+            },
+        };
+        let res: EscrowedTokensResponse =
+            from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res.tokens
+    };
+
+    let instantiate_msg = InstantiateMsg {
+        name: "ERC721".into(),
+        symbol: "ERC721".into(),
+        public_key: signer_public_key(),
+        mint_policy: MintPolicy::Unlimited {},
+        minter: None,
+        token_id_namespace: 0,
+        bridge_mode: BridgeMode::LockRelease,
+    };
+    let res = instantiate(deps.as_mut(), env.clone(), info.clone(), instantiate_msg);
+    assert!(res.is_ok());
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "80001".into(),
+        remote_contract.into(),
+    );
+    let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, 0);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+    assert!(res.is_ok());
+
+    // the token stays here, held by the contract
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        transfer_crosschain_msg("80001", 0),
+    );
+    assert!(res.is_ok());
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        crosschain_call_reply(CREATE_OUTBOUND_REPLY_ID, 1),
+    );
+    assert!(res.is_ok());
+    let owner_of = get_nft_owner_of(deps.as_ref(), env.clone(), "0".into()).unwrap();
+    assert_eq!(owner_of.owner, env.contract.address.to_string());
+    assert_eq!(
+        list_escrowed(deps.as_ref()),
+        vec![EscrowedToken {
+            token_id: 0,
+            owner: SENDER.into(),
+        }]
+    );
+
+    // a failed delivery releases it to the owner
+    let res = do_sudo(&mut deps, env.clone(), ack_msg(1, false));
+    assert!(res.is_ok());
+    let owner_of = get_nft_owner_of(deps.as_ref(), env.clone(), "0".into()).unwrap();
+    assert_eq!(owner_of.owner, SENDER);
+    assert!(list_escrowed(deps.as_ref()).is_empty());
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        transfer_crosschain_msg("80001", 0),
+    );
+    assert!(res.is_ok());
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        crosschain_call_reply(CREATE_OUTBOUND_REPLY_ID, 2),
+    );
+    assert!(res.is_ok());
+    let res = do_sudo(&mut deps, env.clone(), ack_msg(2, true));
+    assert!(res.is_ok());
+    assert_eq!(list_escrowed(deps.as_ref()).len(), 1);
+
+    // coming back releases the escrowed token instead of minting a new one
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        receive_msg("80001", remote_contract, 5, 0, OTHER_SENDER),
+    );
+    assert!(res.is_ok());
+    let owner_of = get_nft_owner_of(deps.as_ref(), env.clone(), "0".into()).unwrap();
+    assert_eq!(owner_of.owner, OTHER_SENDER);
+    assert!(list_escrowed(deps.as_ref()).is_empty());
+    let nft_info = get_nft_info(deps.as_ref(), env.clone(), "0".into()).unwrap();
+    assert_eq!(nft_info.token_uri, Some(TOKEN_URI.into()));
+
+    // a token of another namespace was never escrowed here and is not minted
+    let foreign_id = namespaced_token_id(3, 0);
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        receive_msg("80001", remote_contract, 6, foreign_id, OTHER_SENDER),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::NotEscrowed {
+            token_id: foreign_id
+        }
+    );
+    assert!(get_nft_owner_of(deps.as_ref(), env, foreign_id.to_string()).is_err());
+}
+
+#[test]
+fn test_batch_transfer_crosschain() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+    let remote_contract = "0xc27CE28850774288B3EF678c4550161346944152";
+    let batch_msg = |token_ids: Vec<u64>| Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::TransferCrossChainBatch {
+            dst_chain_id: "80001".into(),
+            token_ids,
+            recipient: "0x1C609537a32630c054202e2B089B9Da268667C5D".to_string(),
+            request_metadata: transfer_request_metadata(),
+        },
+    };
+
+    do_instantiate_with_policy(deps.as_mut(), MintPolicy::Unlimited {});
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "80001".into(),
+        remote_contract.into(),
+    );
+    let payload_version_msg = |version: u8| Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::SetPayloadVersion {
+            chain_id: "80001".into(),
+            version,
+        },
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        payload_version_msg(PAYLOAD_VERSION + 1),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::UnsupportedPayloadVersion {
+            version: PAYLOAD_VERSION + 1
+        }
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        payload_version_msg(PAYLOAD_VERSION),
+    );
+    assert!(res.is_ok());
+    for nonce in 0..3 {
+        let mint_msg = mint_token_msg(&env, SENDER, TOKEN_URI, nonce);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), mint_msg);
+        assert!(res.is_ok());
+    }
+    let mint_msg = mint_token_msg(&env, OTHER_SENDER, OTHER_TOKEN_URI, 0);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        mint_msg,
+    );
+    assert!(res.is_ok());
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), batch_msg(vec![]));
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InvalidBatchSize {
+            size: 0,
+            max: MAX_BATCH_SIZE
+        }
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        batch_msg(vec![0, 1, 0]),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::DuplicateToken { token_id: 0 }
+    );
+    // token 3 belongs to someone else, so nothing moves
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        batch_msg(vec![0, 3]),
+    );
+    assert!(res.is_err());
+    assert!(get_nft_info(deps.as_ref(), env.clone(), "0".into()).is_ok());
+
+    let res = execute(deps.as_mut(), env.clone(), info, batch_msg(vec![0, 2])).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    match &res.messages[0].msg {
+        CosmosMsg::Custom(RouterMsg::CrosschainCall { request_packet, .. }) => {
+            let packet = decode(&[ParamType::String, ParamType::Bytes], request_packet).unwrap();
+            let payload = packet[1].clone().into_bytes().unwrap();
+            let (version, message_type, body) = split_envelope(&payload).unwrap().unwrap();
+            assert_eq!(
+                (version, message_type),
+                (PAYLOAD_VERSION, MESSAGE_TYPE_BATCH_TRANSFER)
+            );
+            let params = decode(&[BatchTransferParams::get_params_types()], &body).unwrap();
+            let params = params[0].clone().into_tuple().unwrap();
+            assert_eq!(
+                params[0],
+                Token::Array(vec![Token::Uint(0.into()), Token::Uint(2.into())])
+            );
+        }
+        _ => panic!("expected a CrosschainCall"),
+    }
+    assert!(get_nft_info(deps.as_ref(), env.clone(), "0".into()).is_err());
+    assert!(get_nft_info(deps.as_ref(), env.clone(), "2".into()).is_err());
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        crosschain_call_reply(CREATE_OUTBOUND_REPLY_ID, 4),
+    );
+    assert!(res.is_ok());
+    assert_eq!(get_transfer(deps.as_ref(), env.clone(), 4).tokens.len(), 2);
+
+    // a failed batch gives every token back
+    let res = do_sudo(&mut deps, env.clone(), ack_msg(4, false));
+    assert!(res.is_ok());
+    for token_id in ["0", "2"] {
+        let owner_of = get_nft_owner_of(deps.as_ref(), env.clone(), token_id.into()).unwrap();
+        assert_eq!(owner_of.owner, SENDER);
+    }
+
+    // and a batch payload coming in mints all of its tokens
+    let payload = encode(&[Token::Tuple(vec![
+        Token::Array(vec![Token::Uint(7.into()), Token::Uint(8.into())]),
+        Token::Bytes(OTHER_SENDER.as_bytes().to_vec()),
+        Token::Array(vec![
+            Token::String(TOKEN_URI.into()),
+            Token::String(OTHER_TOKEN_URI.into()),
+        ]),
+    ])]);
+    let receive_batch_msg = SudoMsg::HandleIReceive {
+        request_sender: remote_contract.into(),
+        src_chain_id: "80001".into(),
+        request_identifier: 9,
+        payload: Binary(payload),
+    };
+    let res = do_sudo(&mut deps, env.clone(), receive_batch_msg);
+    assert!(res.is_ok());
+    let nft_info = get_nft_info(deps.as_ref(), env.clone(), "8".into()).unwrap();
+    assert_eq!(nft_info.token_uri, Some(OTHER_TOKEN_URI.into()));
+    let owner_of = get_nft_owner_of(deps.as_ref(), env, "7".into()).unwrap();
+    assert_eq!(owner_of.owner, OTHER_SENDER);
+}
+
+#[test]
+fn test_chain_types() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+    let near_recipient = "alice.testnet";
+
+    do_instantiate(deps.as_mut());
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "near-testnet".into(),
+        "xerc721.testnet".into(),
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        mint_token_msg(&env, SENDER, TOKEN_URI, 0),
+    );
+    assert!(res.is_ok());
+
+    let set_chain_types_msg = |chain_type: u64| Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::SetChainTypes {
+            chain_type_info: vec![ChainTypeInfo {
+                chain_id: "near-testnet".into(),
+                chain_type,
+            }],
+        },
+    };
+    let get_chain_type = |deps: Deps, chain_id: &str| -> Option<u64> {
+        let query_msg = Cw721QueryMsg::Extension {
+            msg: QueryMsg::GetChainType {
+                chain_id: chain_id.into(),
+            },
+        };
+        from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap()
+    };
+    let transfer_msg = Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::TransferCrossChain {
+            dst_chain_id: "near-testnet".into(),
+            token_id: 0,
+            recipient: near_recipient.into(),
+            request_metadata: transfer_request_metadata(),
+        },
+    };
+
+    // without a type the recipient is read as an EVM address
+    assert_eq!(get_chain_type(deps.as_ref(), "near-testnet"), None);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        transfer_msg.clone(),
+    );
+    assert!(res.is_err());
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        set_chain_types_msg(ChainType::ChainTypeNear.get_chain_code()),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MissingRole {
+            role: Role::BridgeManager
+        }
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        set_chain_types_msg(9),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InvalidChainType {
+            chain_id: "near-testnet".into(),
+            chain_type: 9,
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        set_chain_types_msg(ChainType::ChainTypeNear.get_chain_code()),
+    );
+    assert!(res.is_ok());
+    assert_eq!(
+        get_chain_type(deps.as_ref(), "near-testnet"),
+        Some(ChainType::ChainTypeNear.get_chain_code())
+    );
+
+    let res = execute(deps.as_mut(), env, info, transfer_msg).unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Custom(RouterMsg::CrosschainCall { request_packet, .. }) => {
+            let packet = decode(&[ParamType::String, ParamType::Bytes], request_packet).unwrap();
+            let payload = packet[1].clone().into_bytes().unwrap();
+            // the route was never moved to envelopes
+            let params = decode(&[TransferParams::get_params_types()], &payload).unwrap();
+            let params = params[0].clone().into_tuple().unwrap();
+            assert_eq!(params[1], Token::Bytes(near_recipient.as_bytes().to_vec()));
+        }
+        _ => panic!("expected a CrosschainCall"),
+    }
+}
+
+#[test]
+fn test_unenroll_and_disable_chain() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+    let remote_contract = "0xc27CE28850774288B3EF678c4550161346944152";
+
+    do_instantiate(deps.as_mut());
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "80001".into(),
+        remote_contract.into(),
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        mint_token_msg(&env, SENDER, TOKEN_URI, 0),
+    );
+    assert!(res.is_ok());
+
+    let chain_msg = |msg: ExecuteMsg| Cw721ExecuteMsg::Extension { msg };
+    let is_enabled = |deps: Deps| -> bool {
+        let query_msg = Cw721QueryMsg::Extension {
+            msg: QueryMsg::IsChainEnabled {
+                chain_id: "80001".into(),
             },
-            _ => Err(StdError::NotFound {
-                kind: "isend".into(),
-            }),
         };
-    }
-    // nft should be burned with id 0
-    let response = get_nft_info(deps.as_ref(), env.clone(), "0".into());
-    assert!(response.is_err());
+        from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap()
+    };
+    assert!(is_enabled(deps.as_ref()));
 
-    // try mint after cross chain transfer
-    let mint_msg = ExecuteMsg::MintToken { 
-        token_uri: "https://ipfs.io/ipfs/Qma49KCamwSberpE8whTz3ESk81PoGtLsimmj3YhsWJKPX".to_string(), 
-        signature: "4676479df870e8fdb75b3b24fbe1ca7748bce5bfa9a6621087603d3ac97c1b5a00da3768adaefbf1ecd88d6ab576bb42c1c7325666349ecf7487385286757a09".to_string(), 
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        chain_msg(ExecuteMsg::DisableChain {
+            chain_id: "80001".into(),
+        }),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MissingRole {
+            role: Role::BridgeManager
+        }
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        chain_msg(ExecuteMsg::DisableChain {
+            chain_id: "137".into(),
+        }),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::ChainNotEnrolled {
+            chain_id: "137".into()
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        chain_msg(ExecuteMsg::DisableChain {
+            chain_id: "80001".into(),
+        }),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "disable_chain"),
+            Attribute::new("chain_id", "80001")
+        ]
+    );
+    assert!(!is_enabled(deps.as_ref()));
+
+    // both directions are stopped
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        transfer_crosschain_msg("80001", 0),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::ChainDisabled {
+            chain_id: "80001".into()
+        }
+    );
+    assert!(get_nft_info(deps.as_ref(), env.clone(), "0".into()).is_ok());
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        receive_msg("80001", remote_contract, 3, 42, OTHER_SENDER),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::ChainDisabled {
+            chain_id: "80001".into()
+        }
+    );
+    assert!(get_nft_info(deps.as_ref(), env.clone(), "42".into()).is_err());
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        chain_msg(ExecuteMsg::EnableChain {
+            chain_id: "80001".into(),
+        }),
+    );
+    assert!(res.is_ok());
+    assert!(is_enabled(deps.as_ref()));
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        transfer_crosschain_msg("80001", 0),
+    );
+    assert!(res.is_ok());
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        chain_msg(ExecuteMsg::UnenrollRemoteContract {
+            chain_id: "80001".into(),
+        }),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "unenroll_remote_contract"),
+            Attribute::new("chain_id", "80001")
+        ]
+    );
+    let query_msg = Cw721QueryMsg::Extension {
+        msg: QueryMsg::GetRemoteContract {
+            chain_id: "80001".into(),
+        },
     };
-    let mint_msg = Cw721ExecuteMsg::Extension{ msg: mint_msg };
-    let res = execute(deps.as_mut(), env.clone(), mock_info("router1apapk9zfz3rp4x87fsm6h0s3zd0wlmkz0fx8tx", &[]), mint_msg.clone());
+    assert!(query(deps.as_ref(), env.clone(), query_msg).is_err());
+    assert!(!is_enabled(deps.as_ref()));
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        chain_msg(ExecuteMsg::UnenrollRemoteContract {
+            chain_id: "80001".into(),
+        }),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::ChainNotEnrolled {
+            chain_id: "80001".into()
+        }
+    );
+
+    // re-enrolling does not bring back the disabled flag or the chain type
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        chain_msg(ExecuteMsg::SetChainTypes {
+            chain_type_info: vec![ChainTypeInfo {
+                chain_id: "80001".into(),
+                chain_type: ChainType::ChainTypeEvm.get_chain_code(),
+            }],
+        }),
+    );
     assert!(res.is_ok());
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "80001".into(),
+        remote_contract.into(),
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        chain_msg(ExecuteMsg::DisableChain {
+            chain_id: "80001".into(),
+        }),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        chain_msg(ExecuteMsg::UnenrollRemoteContract {
+            chain_id: "80001".into(),
+        }),
+    )
+    .unwrap();
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        "80001".into(),
+        remote_contract.into(),
+    );
+    assert!(is_enabled(deps.as_ref()));
+    let query_msg = Cw721QueryMsg::Extension {
+        msg: QueryMsg::GetChainType {
+            chain_id: "80001".into(),
+        },
+    };
+    let chain_type: Option<u64> =
+        from_binary(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+    assert_eq!(chain_type, None);
+}
 
-    let response = get_nft_info(deps.as_ref(), env.clone(), "1".into());
-    assert!(response.is_ok());
+#[test]
+fn test_list_remote_contracts() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+
+    do_instantiate(deps.as_mut());
+    let contracts: Vec<RemoteContract> = ["137", "43113", "80001"]
+        .iter()
+        .map(|chain_id| RemoteContract {
+            chain_id: chain_id.to_string(),
+            remote_address: format!("0x{:0>40}", chain_id),
+        })
+        .collect();
+    let enroll_msg = Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::EnrollRemoteContracts {
+            contracts: contracts.clone(),
+        },
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        enroll_msg.clone(),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MissingRole {
+            role: Role::BridgeManager
+        }
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        Cw721ExecuteMsg::Extension {
+            msg: ExecuteMsg::EnrollRemoteContracts { contracts: vec![] },
+        },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::NoRemoteContracts {});
+    let mut duplicated = contracts.clone();
+    duplicated.push(contracts[1].clone());
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        Cw721ExecuteMsg::Extension {
+            msg: ExecuteMsg::EnrollRemoteContracts {
+                contracts: duplicated,
+            },
+        },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::DuplicateChain {
+            chain_id: "43113".into()
+        }
+    );
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), enroll_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "enroll_remote_contracts"),
+            Attribute::new("chain_id", "137"),
+            Attribute::new("chain_id", "43113"),
+            Attribute::new("chain_id", "80001")
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        Cw721ExecuteMsg::Extension {
+            msg: ExecuteMsg::SetChainTypes {
+                chain_type_info: vec![ChainTypeInfo {
+                    chain_id: "43113".into(),
+                    chain_type: ChainType::ChainTypeEvm.get_chain_code(),
+                }],
+            },
+        },
+    );
+    assert!(res.is_ok());
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        Cw721ExecuteMsg::Extension {
+            msg: ExecuteMsg::SetPayloadVersion {
+                chain_id: "43113".into(),
+                version: PAYLOAD_VERSION,
+            },
+        },
+    );
+    assert!(res.is_ok());
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        Cw721ExecuteMsg::Extension {
+            msg: ExecuteMsg::DisableChain {
+                chain_id: "80001".into(),
+            },
+        },
+    );
+    assert!(res.is_ok());
+
+    let list_remote_contracts = |deps: Deps, start_after: Option<&str>, limit: Option<u32>| {
+        let query_msg = Cw721QueryMsg::Extension {
+            msg: QueryMsg::ListRemoteContracts {
+                start_after: start_after.map(String::from),
+                limit,
+            },
+        };
+        let res: RemoteContractsResponse =
+            from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res.contracts
+    };
+
+    let page = list_remote_contracts(deps.as_ref(), None, Some(2));
+    assert_eq!(
+        page,
+        vec![
+            RemoteContractInfo {
+                chain_id: "137".into(),
+                remote_address: contracts[0].remote_address.clone(),
+                chain_type: None,
+                enabled: true,
+                payload_version: LEGACY_PAYLOAD_VERSION,
+            },
+            RemoteContractInfo {
+                chain_id: "43113".into(),
+                remote_address: contracts[1].remote_address.clone(),
+                chain_type: Some(ChainType::ChainTypeEvm.get_chain_code()),
+                enabled: true,
+                payload_version: PAYLOAD_VERSION,
+            },
+        ]
+    );
+    let page = list_remote_contracts(deps.as_ref(), Some("43113"), None);
+    assert_eq!(
+        page,
+        vec![RemoteContractInfo {
+            chain_id: "80001".into(),
+            remote_address: contracts[2].remote_address.clone(),
+            chain_type: None,
+            enabled: false,
+            payload_version: LEGACY_PAYLOAD_VERSION,
+        }]
+    );
+}
+
+#[test]
+fn test_unknown_chains_and_check_transfer() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let info = mock_info(SENDER, &[]);
+    let remote_contract = "0xc27CE28850774288B3EF678c4550161346944152";
+
+    do_instantiate(deps.as_mut());
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        mint_token_msg(&env, SENDER, TOKEN_URI, 0),
+    );
+    assert!(res.is_ok());
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        transfer_crosschain_msg("80001", 0),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::UnknownDestination {
+            chain_id: "80001".into()
+        }
+    );
+    assert!(get_nft_info(deps.as_ref(), env.clone(), "0".into()).is_ok());
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        receive_msg("80001", remote_contract, 3, 42, OTHER_SENDER),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::UnknownSource {
+            chain_id: "80001".into()
+        }
+    );
+
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "80001".into(),
+        remote_contract.into(),
+    );
+    let check_transfer = |deps: Deps, sender: &str, request_metadata: RequestMetaData| {
+        let query_msg = Cw721QueryMsg::Extension {
+            msg: QueryMsg::CheckTransfer {
+                sender: sender.into(),
+                dst_chain_id: "80001".into(),
+                token_ids: vec![0],
+                recipient: "0x1C609537a32630c054202e2B089B9Da268667C5D".into(),
+                request_metadata,
+            },
+        };
+        let res: CheckTransferResponse =
+            from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res
+    };
+
+    assert_eq!(
+        check_transfer(deps.as_ref(), SENDER, transfer_request_metadata()),
+        CheckTransferResponse {
+            ok: true,
+            error: None
+        }
+    );
+    let res = check_transfer(deps.as_ref(), OTHER_SENDER, transfer_request_metadata());
+    assert!(!res.ok);
+    assert_eq!(
+        res.error,
+        Some(ContractError::Cw721(cw721_base::ContractError::Unauthorized {}).to_string())
+    );
+    let read_call = RequestMetaData {
+        is_read_call: true,
+        ..transfer_request_metadata()
+    };
+    let res = check_transfer(deps.as_ref(), SENDER, read_call.clone());
+    assert_eq!(
+        res.error,
+        Some(
+            ContractError::InvalidRequestMetadata {
+                reason: "a transfer can not be a read call".into()
+            }
+            .to_string()
+        )
+    );
+    let no_ack = RequestMetaData {
+        ack_type: AckType::NoAck,
+        ..transfer_request_metadata()
+    };
+    assert!(!check_transfer(deps.as_ref(), SENDER, no_ack).ok);
+    let error_ack_only = RequestMetaData {
+        ack_type: AckType::AckOnError,
+        ..transfer_request_metadata()
+    };
+    assert_eq!(
+        check_transfer(deps.as_ref(), SENDER, error_ack_only).error,
+        Some(
+            ContractError::InvalidRequestMetadata {
+                reason: "ack_type must be ack_on_both".into()
+            }
+            .to_string()
+        )
+    );
+
+    // the query answers what the transfer does
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        Cw721ExecuteMsg::Extension {
+            msg: ExecuteMsg::TransferCrossChain {
+                dst_chain_id: "80001".into(),
+                token_id: 0,
+                recipient: "0x1C609537a32630c054202e2B089B9Da268667C5D".into(),
+                request_metadata: read_call,
+            },
+        },
+    );
+    assert!(res.is_err());
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        transfer_crosschain_msg("80001", 0),
+    );
+    assert!(res.is_ok());
+    assert!(!check_transfer(deps.as_ref(), SENDER, transfer_request_metadata()).ok);
+}
+
+#[test]
+fn test_inbound_envelopes() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let remote_contract = "0xc27CE28850774288B3EF678c4550161346944152";
+
+    do_instantiate(deps.as_mut());
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(SENDER, &[]),
+        "80001".into(),
+        remote_contract.into(),
+    );
+    let envelope_msg = |request_identifier: u64, payload: Vec<u8>| SudoMsg::HandleIReceive {
+        request_sender: remote_contract.into(),
+        src_chain_id: "80001".into(),
+        request_identifier,
+        payload: Binary(payload),
+    };
+    let cosmos = ChainType::ChainTypeCosmos.get_chain_code();
+
+    let transfer = CrossChainMessage::Transfer(TransferParams {
+        nft_id: 42,
+        recipient: OTHER_SENDER.into(),
+        uri: TOKEN_URI.into(),
+    });
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        envelope_msg(1, transfer.encode_envelope(cosmos).unwrap()),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&Attribute::new("token_ids", "42")));
+    let owner_of = get_nft_owner_of(deps.as_ref(), env.clone(), "42".into()).unwrap();
+    assert_eq!(owner_of.owner, OTHER_SENDER);
+
+    let update = CrossChainMessage::MetadataUpdate(MetadataUpdateParams {
+        nft_id: 42,
+        uri: OTHER_TOKEN_URI.into(),
+    });
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        envelope_msg(2, update.encode_envelope(cosmos).unwrap()),
+    );
+    assert!(res.is_ok());
+    let nft_info = get_nft_info(deps.as_ref(), env.clone(), "42".into()).unwrap();
+    assert_eq!(nft_info.token_uri, Some(OTHER_TOKEN_URI.into()));
+
+    // a token minted here did not come from the remote, so it keeps its uri
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(SENDER, &[]),
+        mint_token_msg(&env, SENDER, TOKEN_URI, 0),
+    );
+    assert!(res.is_ok());
+    let update = CrossChainMessage::MetadataUpdate(MetadataUpdateParams {
+        nft_id: 0,
+        uri: OTHER_TOKEN_URI.into(),
+    });
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        envelope_msg(3, update.encode_envelope(cosmos).unwrap()),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::NotTokenOrigin {
+            token_id: 0,
+            chain_id: "80001".into()
+        }
+    );
+    let nft_info = get_nft_info(deps.as_ref(), env.clone(), "0".into()).unwrap();
+    assert_eq!(nft_info.token_uri, Some(TOKEN_URI.into()));
+
+    // a token this chain does not hold is reported, not updated
+    let update = CrossChainMessage::MetadataUpdate(MetadataUpdateParams {
+        nft_id: 999,
+        uri: OTHER_TOKEN_URI.into(),
+    });
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        envelope_msg(8, update.encode_envelope(cosmos).unwrap()),
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("status", "skipped")));
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("reason", "unknown_token")));
+
+    // an enrolled remote sends admin commands only once the owner allows it
+    let pause = CrossChainMessage::Admin(AdminCommand::Pause);
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        envelope_msg(9, pause.encode_envelope(cosmos).unwrap()),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::NotAdminSource {
+            chain_id: "80001".into()
+        }
+    );
+    let admin_source_msg = Cw721ExecuteMsg::Extension {
+        msg: ExecuteMsg::SetAdminSource {
+            chain_id: "80001".into(),
+            allowed: true,
+        },
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OTHER_SENDER, &[]),
+        admin_source_msg.clone(),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::InvalidOwner {});
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(SENDER, &[]),
+        admin_source_msg,
+    );
+    assert!(res.is_ok());
+    let query_msg = Cw721QueryMsg::Extension {
+        msg: QueryMsg::IsAdminSource {
+            chain_id: "80001".into(),
+        },
+    };
+    let admin_source: bool =
+        from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+    assert!(admin_source);
+
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        envelope_msg(4, pause.encode_envelope(cosmos).unwrap()),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&Attribute::new("command", "pause")));
+    let query_msg = Cw721QueryMsg::Extension {
+        msg: QueryMsg::IsPaused {},
+    };
+    let paused: bool =
+        from_binary(&query(deps.as_ref(), env.clone(), query_msg.clone()).unwrap()).unwrap();
+    assert!(paused);
+    let unpause = CrossChainMessage::Admin(AdminCommand::Unpause);
+    let res = do_sudo(
+        &mut deps,
+        env.clone(),
+        envelope_msg(5, unpause.encode_envelope(cosmos).unwrap()),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::RemoteUnpause {});
+    let paused: bool = from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+    assert!(paused);
+
+    // later versions and message types are rejected, so the source gets an error ack
+    let mut payload = transfer.encode_envelope(cosmos).unwrap();
+    // the version and message type are the last bytes of the first two words
+    payload[31] = PAYLOAD_VERSION + 1;
+    let res = do_sudo(&mut deps, env.clone(), envelope_msg(6, payload.clone()));
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::UnsupportedPayloadVersion {
+            version: PAYLOAD_VERSION + 1
+        }
+    );
+    payload[31] = PAYLOAD_VERSION;
+    payload[63] = 9;
+    let res = do_sudo(&mut deps, env, envelope_msg(7, payload));
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::UnknownMessageType { message_type: 9 }
+    );
+}
+
+#[test]
+fn test_malformed_inbound_payload_is_an_error() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let remote_contract = "0xc27CE28850774288B3EF678c4550161346944152";
+
+    do_instantiate(deps.as_mut());
+    set_remote_contract(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(SENDER, &[]),
+        "80001".into(),
+        remote_contract.into(),
+    );
+    // a recipient that is not UTF-8
+    let transfer_params = Token::Tuple(vec![
+        Token::Uint(42.into()),
+        Token::Bytes(vec![0xff, 0xfe]),
+        Token::String(TOKEN_URI.into()),
+    ]);
+    let msg = SudoMsg::HandleIReceive {
+        request_sender: remote_contract.into(),
+        src_chain_id: "80001".into(),
+        request_identifier: 1,
+        payload: Binary(encode(&[transfer_params])),
+    };
+    match do_sudo(&mut deps, env, msg).unwrap_err() {
+        ContractError::PayloadDecode { reason } => {
+            assert!(reason.contains("recipient"), "{}", reason)
+        }
+        err => panic!("expected a payload decode error, got {:?}", err),
+    }
 }
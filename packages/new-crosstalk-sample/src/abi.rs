@@ -0,0 +1,84 @@
+// readers for the fields of decoded ABI tuples, errors name the struct and
+// field that did not match
+
+use cosmwasm_std::{StdError, StdResult};
+use router_wasm_bindings::ethabi::{ethereum_types::U256, Token};
+
+fn field_err(target: &str, field: &str, msg: impl std::fmt::Display) -> StdError {
+    StdError::parse_err(target, format!("{}: {}", field, msg))
+}
+
+/// The fields of `tuple`, checked to be exactly `N` long.
+pub(crate) fn fields<const N: usize>(target: &str, tuple: Vec<Token>) -> StdResult<[Token; N]> {
+    let len = tuple.len();
+    tuple
+        .try_into()
+        .map_err(|_| StdError::parse_err(target, format!("expected {} fields, got {}", N, len)))
+}
+
+pub(crate) fn uint(target: &str, field: &str, token: Token) -> StdResult<U256> {
+    token
+        .into_uint()
+        .ok_or_else(|| field_err(target, field, "expected uint"))
+}
+
+pub(crate) fn uint_u8(target: &str, field: &str, token: Token) -> StdResult<u8> {
+    let value = uint(target, field, token)?;
+    if value > U256::from(u8::MAX) {
+        return Err(field_err(target, field, "does not fit in 8 bits"));
+    }
+    Ok(value.as_u32() as u8)
+}
+
+pub(crate) fn uint_u64(target: &str, field: &str, token: Token) -> StdResult<u64> {
+    let value = uint(target, field, token)?;
+    if value > U256::from(u64::MAX) {
+        return Err(field_err(target, field, "does not fit in 64 bits"));
+    }
+    Ok(value.as_u64())
+}
+
+pub(crate) fn uint_u128(target: &str, field: &str, token: Token) -> StdResult<u128> {
+    let value = uint(target, field, token)?;
+    if value > U256::from(u128::MAX) {
+        return Err(field_err(target, field, "does not fit in 128 bits"));
+    }
+    Ok(value.as_u128())
+}
+
+pub(crate) fn bytes(target: &str, field: &str, token: Token) -> StdResult<Vec<u8>> {
+    token
+        .into_bytes()
+        .ok_or_else(|| field_err(target, field, "expected bytes"))
+}
+
+pub(crate) fn string(target: &str, field: &str, token: Token) -> StdResult<String> {
+    token
+        .into_string()
+        .ok_or_else(|| field_err(target, field, "expected string"))
+}
+
+pub(crate) fn array(target: &str, field: &str, token: Token) -> StdResult<Vec<Token>> {
+    token
+        .into_array()
+        .ok_or_else(|| field_err(target, field, "expected array"))
+}
+
+/// Reads every element of an array field with `read`, errors carry the element index.
+pub(crate) fn array_of<T>(
+    target: &str,
+    field: &str,
+    token: Token,
+    read: impl Fn(&str, &str, Token) -> StdResult<T>,
+) -> StdResult<Vec<T>> {
+    array(target, field, token)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, element)| read(target, &format!("{}[{}]", field, i), element))
+        .collect()
+}
+
+/// Maps an address conversion error to the field it was read from.
+pub(crate) fn address(target: &str, field: &str, result: StdResult<String>) -> StdResult<String> {
+    result.map_err(|err| field_err(target, field, err))
+}
@@ -0,0 +1,194 @@
+use crate::{Deserialize, Serialize};
+use cosmwasm_std::{from_binary, Binary, Response, StdError, StdResult, Storage, SubMsgResult};
+use cw0::Expiration;
+use cw_storage_plus::Map;
+use ed25519_zebra::VerificationKey;
+use router_wasm_bindings::{
+    ethabi::{encode, ethereum_types::U256, Token},
+    types::{AckType, ChainType, CrosschainRequestResponse, RequestMetaData},
+    Bytes,
+};
+use schemars::JsonSchema;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum InboundRequestError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("No remote contract registered for source chain {chain_id}")]
+    UnknownSource { chain_id: String },
+
+    #[error("Invalid request sender {sender}")]
+    InvalidRequestSender { sender: String },
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum PublicKeyError {
+    #[error("Malformed public key: {reason}")]
+    Malformed { reason: String },
+
+    #[error("Invalid public key length: expected {expected} bytes, got {actual}")]
+    InvalidLength { expected: String, actual: usize },
+}
+
+/// Router `ChainType` code of a chain, see `ChainType::get_chain_code`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChainTypeInfo {
+    pub chain_id: String,
+    pub chain_type: u64,
+}
+
+/// Whether `chain_type` is the code of a Router `ChainType`.
+pub fn is_known_chain_type(chain_type: u64) -> bool {
+    [
+        ChainType::ChainTypeEvm,
+        ChainType::ChainTypeCosmos,
+        ChainType::ChainTypePolkadot,
+        ChainType::ChainTypeSolano,
+        ChainType::ChainTypeNear,
+    ]
+    .iter()
+    .any(|known| known.get_chain_code() == chain_type)
+}
+
+/// Router `ChainType` code `chain_types` holds for `chain_id`, `default` when
+/// none is set.
+pub fn chain_type_of(
+    storage: &dyn Storage,
+    chain_types: &Map<String, u64>,
+    chain_id: &str,
+    default: ChainType,
+) -> StdResult<u64> {
+    Ok(chain_types
+        .may_load(storage, chain_id.to_string())?
+        .unwrap_or_else(|| default.get_chain_code()))
+}
+
+/// Request identifier Router assigned to a `CrosschainCall` submessage, read
+/// from the data of its reply.
+pub fn crosschain_request_identifier(result: SubMsgResult) -> Result<u64, String> {
+    let data = match result {
+        SubMsgResult::Ok(response) => response.data,
+        SubMsgResult::Err(err) => return Err(err),
+    };
+    let data = data.ok_or_else(|| "missing request identifier".to_string())?;
+    let request: CrosschainRequestResponse = from_binary(&data).map_err(|err| err.to_string())?;
+    Ok(request.request_identifier)
+}
+
+/// Checks an inbound request comes from the contract `remotes` holds for its
+/// source chain.
+pub fn check_request_sender(
+    storage: &dyn Storage,
+    remotes: &Map<String, String>,
+    src_chain_id: &str,
+    request_sender: &str,
+) -> Result<(), InboundRequestError> {
+    let remote = remotes
+        .may_load(storage, src_chain_id.to_string())?
+        .ok_or_else(|| InboundRequestError::UnknownSource {
+            chain_id: src_chain_id.to_string(),
+        })?;
+    if remote != request_sender {
+        return Err(InboundRequestError::InvalidRequestSender {
+            sender: request_sender.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Response to a `HandleIReceive`, its data, the ABI encoded source chain id,
+/// is the ack payload Router carries back to the source.
+pub fn inbound_ack_response<T>(src_chain_id: &str, request_identifier: u64) -> Response<T> {
+    Response::new()
+        .add_attribute("action", "handle_receive")
+        .add_attribute("src_chain_id", src_chain_id)
+        .add_attribute("request_identifier", request_identifier.to_string())
+        .set_data(Binary(encode(&[Token::String(src_chain_id.to_string())])))
+}
+
+/// Records an inbound request in `processed`, `false` when it already was:
+/// Router retries a delivery until it is acked, so the same request can
+/// arrive more than once.
+pub fn record_inbound_request(
+    storage: &mut dyn Storage,
+    processed: &Map<(String, u64), bool>,
+    src_chain_id: &str,
+    request_identifier: u64,
+) -> StdResult<bool> {
+    let key = (src_chain_id.to_string(), request_identifier);
+    if processed.has(storage, key.clone()) {
+        return Ok(false);
+    }
+    processed.save(storage, key, &true)?;
+    Ok(true)
+}
+
+/// Decodes a hex voucher signer key and checks it is a valid ed25519 point,
+/// so that a key no signature can match is refused when it is set.
+pub fn decode_ed25519_public_key(public_key: &str) -> Result<Binary, PublicKeyError> {
+    let public_key = hex::decode(public_key.trim_start_matches("0x")).map_err(|err| {
+        PublicKeyError::Malformed {
+            reason: err.to_string(),
+        }
+    })?;
+    let bytes: [u8; 32] =
+        public_key
+            .as_slice()
+            .try_into()
+            .map_err(|_| PublicKeyError::InvalidLength {
+                expected: "32".to_string(),
+                actual: public_key.len(),
+            })?;
+    VerificationKey::try_from(bytes).map_err(|err| PublicKeyError::Malformed {
+        reason: err.to_string(),
+    })?;
+    Ok(Binary(public_key))
+}
+
+/// Why `request_metadata` can not carry a transfer: a read call never
+/// executes on the destination, and only `ack_on_both` settles the transfer
+/// either way, refunding a failure and letting a delivery leave pending.
+pub fn check_request_metadata(request_metadata: &RequestMetaData) -> Result<(), String> {
+    if request_metadata.is_read_call {
+        return Err("a transfer can not be a read call".to_string());
+    }
+    if request_metadata.ack_type != AckType::AckOnBoth {
+        return Err("ack_type must be ack_on_both".to_string());
+    }
+    Ok(())
+}
+
+/// `domain || abi.encode(voucher)`, the bytes a mint voucher signer signs
+/// over. Every contract has its own domain, which keeps a voucher for one
+/// from being valid for anything else.
+pub fn mint_voucher_bytes(domain: &[u8], voucher: Token) -> Bytes {
+    let mut bytes: Bytes = domain.to_vec();
+    bytes.append(&mut encode(&[voucher]));
+    bytes
+}
+
+/// `expires` as the `(expiry_kind, expiry_value)` voucher fields: 0 never,
+/// 1 at a block height, 2 at a time in nanoseconds.
+pub fn expiration_tokens(expires: &Expiration) -> [Token; 2] {
+    let (expiry_kind, expiry_value) = match expires {
+        Expiration::Never {} => (0u8, 0u64),
+        Expiration::AtHeight(height) => (1u8, *height),
+        Expiration::AtTime(time) => (2u8, time.nanos()),
+    };
+    [
+        Token::Uint(U256::from(expiry_kind)),
+        Token::Uint(U256::from(expiry_value)),
+    ]
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStatus {
+    // left this chain, waiting for the Router ack
+    Pending,
+    Delivered,
+    // delivery failed and the tokens were given back to the sender
+    Refunded,
+}
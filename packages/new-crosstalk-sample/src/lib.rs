@@ -1,7 +1,13 @@
+mod abi;
+pub mod common;
 pub mod ping_pong;
 pub mod test_dapp;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod xerc1155;
 pub mod xerc20;
 pub mod xerc721;
 
 pub use serde::{Deserialize, Serialize};
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,82 @@
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    to_binary, Binary, Coin, DepsMut, OwnedDeps, QuerierWrapper, Reply, SubMsgResponse,
+    SubMsgResult, Uint128,
+};
+use router_wasm_bindings::types::{AckType, CrosschainRequestResponse, RequestMetaData};
+use router_wasm_bindings::{RouterQuery, SudoMsg};
+
+/// Runs `f` with `deps` as the `DepsMut<RouterQuery>` the sudo and migrate
+/// entry points take.
+pub fn with_router_deps<R>(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    f: impl FnOnce(DepsMut<RouterQuery>) -> R,
+) -> R {
+    let querier: MockQuerier<RouterQuery> = MockQuerier::new(&[]);
+    f(DepsMut {
+        storage: &mut deps.storage,
+        api: &deps.api,
+        querier: QuerierWrapper::new(&querier),
+    })
+}
+
+/// Metadata a transfer is accepted with: acked either way, not a read call.
+pub fn transfer_request_metadata() -> RequestMetaData {
+    RequestMetaData {
+        dest_gas_limit: 0,
+        ack_gas_limit: 0,
+        dest_gas_price: 0,
+        ack_gas_price: 0,
+        relayer_fee: Uint128::zero(),
+        ack_type: AckType::AckOnBoth,
+        is_read_call: false,
+        asm_address: "".into(),
+    }
+}
+
+/// What Router answers to a `CrosschainCall` submessage.
+pub fn crosschain_call_reply(reply_id: u64, request_identifier: u64) -> Reply {
+    Reply {
+        id: reply_id,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(to_binary(&CrosschainRequestResponse { request_identifier }).unwrap()),
+        }),
+    }
+}
+
+/// `MsgInstantiateContractResponse { contract_address }` as protobuf.
+pub fn instantiate_reply(reply_id: u64, contract_address: &str) -> Reply {
+    let mut data = vec![0x0a, contract_address.len() as u8];
+    data.extend_from_slice(contract_address.as_bytes());
+    Reply {
+        id: reply_id,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(Binary(data)),
+        }),
+    }
+}
+
+pub fn receive_msg(
+    src_chain_id: &str,
+    request_sender: &str,
+    request_identifier: u64,
+    payload: Vec<u8>,
+) -> SudoMsg {
+    SudoMsg::HandleIReceive {
+        request_sender: request_sender.into(),
+        src_chain_id: src_chain_id.into(),
+        request_identifier,
+        payload: Binary(payload),
+    }
+}
+
+pub fn ack_msg(request_identifier: u64, exec_flag: bool) -> SudoMsg {
+    SudoMsg::HandleIAck {
+        request_identifier,
+        exec_flag,
+        exec_data: Binary::default(),
+        refund_amount: Coin::new(0, "route"),
+    }
+}
@@ -0,0 +1,334 @@
+use crate::{common, xerc1155, xerc20, xerc721};
+use cosmwasm_std::testing::MockStorage;
+use cosmwasm_std::{to_binary, Binary, StdError, SubMsgResponse, SubMsgResult};
+use cw_storage_plus::Map;
+use proptest::prelude::*;
+use router_wasm_bindings::ethabi::{decode, encode, ethereum_types::U256, ParamType, Token};
+use router_wasm_bindings::types::{ChainType, CrosschainRequestResponse};
+
+fn cosmos() -> u64 {
+    ChainType::ChainTypeCosmos.get_chain_code()
+}
+
+fn evm() -> u64 {
+    ChainType::ChainTypeEvm.get_chain_code()
+}
+
+// encodes `token`, decodes it back as `param` and returns the tuple fields
+fn round_trip(token: Token, param: ParamType) -> Vec<Token> {
+    let encoded = encode(&[token]);
+    let mut decoded = decode(&[param], &encoded).unwrap();
+    decoded.remove(0).into_tuple().unwrap()
+}
+
+fn evm_address() -> impl Strategy<Value = String> {
+    prop::array::uniform20(any::<u8>()).prop_map(|bytes| format!("0x{}", hex::encode(bytes)))
+}
+
+// any ABI token of the kinds used by the payloads, arrays included
+fn any_token() -> impl Strategy<Value = Token> {
+    let leaf = prop_oneof![
+        any::<u128>().prop_map(|value| Token::Uint(U256::from(value))),
+        any::<[u8; 32]>().prop_map(|bytes| Token::Uint(U256::from_big_endian(&bytes))),
+        prop::collection::vec(any::<u8>(), 0..40).prop_map(Token::Bytes),
+        ".*".prop_map(Token::String),
+        any::<bool>().prop_map(Token::Bool),
+    ];
+    leaf.prop_recursive(2, 16, 4, |inner| {
+        prop::collection::vec(inner, 0..4).prop_map(Token::Array)
+    })
+}
+
+fn parse_err_msg(err: StdError) -> String {
+    match err {
+        StdError::ParseErr { msg, .. } => msg,
+        err => panic!("expected a parse error, got {:?}", err),
+    }
+}
+
+proptest! {
+    #[test]
+    fn xerc721_transfer_params_round_trip(
+        nft_id in any::<u64>(),
+        recipient in "[a-z0-9]{1,64}",
+        evm_recipient in evm_address(),
+        uri in ".*",
+    ) {
+        for (recipient, chain_type) in [(recipient, cosmos()), (evm_recipient, evm())] {
+            let params = xerc721::TransferParams { nft_id, recipient, uri: uri.clone() };
+            let tuple = round_trip(
+                params.get_evm_encoding(chain_type).unwrap(),
+                xerc721::TransferParams::get_params_types(),
+            );
+            let decoded = xerc721::TransferParams::from_token_tuple(tuple, chain_type).unwrap();
+            prop_assert_eq!(decoded, params);
+        }
+    }
+
+    #[test]
+    fn xerc721_batch_transfer_params_round_trip(
+        nft_ids in prop::collection::vec(any::<u64>(), 0..8),
+        recipient in evm_address(),
+        uri in ".*",
+    ) {
+        let params = xerc721::BatchTransferParams {
+            uris: vec![uri; nft_ids.len()],
+            nft_ids,
+            recipient,
+        };
+        let tuple = round_trip(
+            params.get_evm_encoding(evm()).unwrap(),
+            xerc721::BatchTransferParams::get_params_types(),
+        );
+        let decoded = xerc721::BatchTransferParams::from_token_tuple(tuple, evm()).unwrap();
+        prop_assert_eq!(decoded, params);
+    }
+
+    #[test]
+    fn xerc721_envelope_round_trip(nft_id in any::<u64>(), uri in ".*", pause in any::<bool>()) {
+        let update = xerc721::MetadataUpdateParams { nft_id, uri };
+        let envelope = xerc721::CrossChainMessage::MetadataUpdate(update.clone())
+            .encode_envelope(cosmos())
+            .unwrap();
+        let (version, message_type, body) = xerc721::split_envelope(&envelope).unwrap().unwrap();
+        prop_assert_eq!(version, xerc721::PAYLOAD_VERSION);
+        prop_assert_eq!(message_type, xerc721::MESSAGE_TYPE_METADATA_UPDATE);
+        let mut tokens = decode(&[xerc721::MetadataUpdateParams::get_params_types()], &body).unwrap();
+        let tuple = tokens.remove(0).into_tuple().unwrap();
+        prop_assert_eq!(xerc721::MetadataUpdateParams::from_token_tuple(tuple).unwrap(), update);
+
+        let command = if pause { xerc721::AdminCommand::Pause } else { xerc721::AdminCommand::Unpause };
+        let envelope = xerc721::CrossChainMessage::Admin(command).encode_envelope(cosmos()).unwrap();
+        let (_, message_type, body) = xerc721::split_envelope(&envelope).unwrap().unwrap();
+        prop_assert_eq!(message_type, xerc721::MESSAGE_TYPE_ADMIN);
+        let mut tokens = decode(&[ParamType::Uint(8)], &body).unwrap();
+        prop_assert_eq!(xerc721::AdminCommand::from_token(tokens.remove(0)).unwrap(), command);
+    }
+
+    #[test]
+    fn xerc1155_transfer_params_round_trip(
+        nfts in prop::collection::vec((any::<u128>(), any::<u128>()), 0..8),
+        nft_data in prop::collection::vec(any::<u8>(), 0..64),
+        recipient in evm_address(),
+    ) {
+        let params = xerc1155::TransferParams {
+            nft_ids: nfts.iter().map(|(id, _)| (*id).into()).collect(),
+            nft_amounts: nfts.iter().map(|(_, amount)| (*amount).into()).collect(),
+            nft_data,
+            recipient,
+        };
+        let tuple = round_trip(
+            params.get_evm_encoding(evm()).unwrap(),
+            xerc1155::TransferParams::get_params_types(),
+        );
+        let decoded = xerc1155::TransferParams::from_token_tuple(tuple, evm()).unwrap();
+        prop_assert_eq!(decoded, params);
+    }
+
+    #[test]
+    fn xerc20_transfer_params_round_trip(
+        recipient in prop::collection::vec(any::<u8>(), 0..64),
+        amount in any::<u128>(),
+    ) {
+        let params = xerc20::TransferParams { recipient, amount: amount.into() };
+        let tuple = round_trip(params.get_evm_encoding(), xerc20::TransferParams::get_params_types());
+        prop_assert_eq!(xerc20::TransferParams::from_token_tuple(tuple).unwrap(), params);
+    }
+
+    // a malformed packet gives an error, never a panic
+    #[test]
+    fn decoders_do_not_panic(
+        tuple in prop::collection::vec(any_token(), 0..6),
+        chain_type in 0u64..7,
+    ) {
+        let _ = xerc721::TransferParams::from_token_tuple(tuple.clone(), chain_type);
+        let _ = xerc721::BatchTransferParams::from_token_tuple(tuple.clone(), chain_type);
+        let _ = xerc721::MetadataUpdateParams::from_token_tuple(tuple.clone());
+        let _ = xerc1155::TransferParams::from_token_tuple(tuple.clone(), chain_type);
+        let _ = xerc20::TransferParams::from_token_tuple(tuple.clone());
+        for token in tuple {
+            let _ = xerc721::AdminCommand::from_token(token);
+        }
+    }
+
+    #[test]
+    fn split_envelope_does_not_panic(payload in prop::collection::vec(any::<u8>(), 0..256)) {
+        let _ = xerc721::split_envelope(&payload);
+    }
+
+    // legacy payloads are bare ABI encodings and never look like an envelope
+    #[test]
+    fn legacy_payloads_are_not_envelopes(nft_id in any::<u64>(), recipient in evm_address(), uri in ".*") {
+        let params = xerc721::TransferParams { nft_id, recipient, uri };
+        let payload = encode(&[params.get_evm_encoding(evm()).unwrap()]);
+        prop_assert_eq!(xerc721::split_envelope(&payload).unwrap(), None);
+        let message = xerc721::CrossChainMessage::Transfer(params);
+        prop_assert_eq!(
+            message.encode_payload(evm(), xerc721::LEGACY_PAYLOAD_VERSION).unwrap(),
+            payload
+        );
+    }
+}
+
+#[test]
+fn decoder_errors_name_the_field() {
+    let tuple = vec![
+        Token::Uint(U256::from(1)),
+        Token::String("router1recipient".into()),
+        Token::String("ipfs://uri".into()),
+    ];
+    let err = xerc721::TransferParams::from_token_tuple(tuple, cosmos()).unwrap_err();
+    assert_eq!(parse_err_msg(err), "recipient: expected bytes");
+
+    let tuple = vec![
+        Token::Uint(U256::from(u64::MAX) + 1),
+        Token::Bytes(b"router1recipient".to_vec()),
+        Token::String("ipfs://uri".into()),
+    ];
+    let err = xerc721::TransferParams::from_token_tuple(tuple, cosmos()).unwrap_err();
+    assert_eq!(parse_err_msg(err), "nft_id: does not fit in 64 bits");
+
+    let err = xerc721::TransferParams::from_token_tuple(vec![], cosmos()).unwrap_err();
+    assert_eq!(parse_err_msg(err), "expected 3 fields, got 0");
+
+    let tuple = vec![
+        Token::Array(vec![Token::Uint(U256::from(1)), Token::Bool(true)]),
+        Token::Bytes(b"router1recipient".to_vec()),
+        Token::Array(vec![]),
+    ];
+    let err = xerc721::BatchTransferParams::from_token_tuple(tuple, cosmos()).unwrap_err();
+    assert_eq!(parse_err_msg(err), "nft_ids[1]: expected uint");
+
+    let tuple = vec![
+        Token::Uint(U256::from(1)),
+        Token::Bytes(vec![0xff, 0xfe]),
+        Token::String("ipfs://uri".into()),
+    ];
+    let err = xerc721::TransferParams::from_token_tuple(tuple, cosmos()).unwrap_err();
+    assert!(parse_err_msg(err).starts_with("recipient: "));
+
+    let err = xerc721::AdminCommand::from_token(Token::Uint(U256::from(7))).unwrap_err();
+    assert_eq!(parse_err_msg(err), "command: unknown code 7");
+}
+
+// `abi.encode(TransferParams({ nftIds: [1, 2], nftAmounts: [10, 20], nftData: hex"1234",
+// recipient: abi.encodePacked(0x1C60...7C5D) }))` on an EVM chain
+const XERC1155_TRANSFER_FIXTURE: &str = concat!(
+    "0000000000000000000000000000000000000000000000000000000000000020",
+    "0000000000000000000000000000000000000000000000000000000000000080",
+    "00000000000000000000000000000000000000000000000000000000000000e0",
+    "0000000000000000000000000000000000000000000000000000000000000140",
+    "0000000000000000000000000000000000000000000000000000000000000180",
+    "0000000000000000000000000000000000000000000000000000000000000002",
+    "0000000000000000000000000000000000000000000000000000000000000001",
+    "0000000000000000000000000000000000000000000000000000000000000002",
+    "0000000000000000000000000000000000000000000000000000000000000002",
+    "000000000000000000000000000000000000000000000000000000000000000a",
+    "0000000000000000000000000000000000000000000000000000000000000014",
+    "0000000000000000000000000000000000000000000000000000000000000002",
+    "1234000000000000000000000000000000000000000000000000000000000000",
+    "0000000000000000000000000000000000000000000000000000000000000014",
+    "1c609537a32630c054202e2b089b9da268667c5d000000000000000000000000",
+);
+
+fn xerc1155_fixture_params() -> xerc1155::TransferParams {
+    xerc1155::TransferParams {
+        nft_ids: vec![1u128.into(), 2u128.into()],
+        nft_amounts: vec![10u128.into(), 20u128.into()],
+        nft_data: vec![0x12, 0x34],
+        recipient: "0x1c609537a32630c054202e2b089b9da268667c5d".into(),
+    }
+}
+
+#[test]
+fn xerc1155_codec_matches_evm_fixture() {
+    let fixture = hex::decode(XERC1155_TRANSFER_FIXTURE).unwrap();
+    let params = xerc1155_fixture_params();
+    let encoded = encode(&[params.get_evm_encoding(evm()).unwrap()]);
+    assert_eq!(hex::encode(&encoded), XERC1155_TRANSFER_FIXTURE);
+
+    let mut decoded = decode(&[xerc1155::TransferParams::get_params_types()], &fixture).unwrap();
+    let tuple = decoded.remove(0).into_tuple().unwrap();
+    assert_eq!(
+        xerc1155::TransferParams::from_token_tuple(tuple, evm()).unwrap(),
+        params
+    );
+}
+
+#[test]
+fn xerc1155_length_mismatch_is_an_error() {
+    let params = xerc1155::TransferParams {
+        nft_amounts: vec![10u128.into()],
+        ..xerc1155_fixture_params()
+    };
+    let err = params.get_evm_encoding(evm()).unwrap_err();
+    assert_eq!(parse_err_msg(err), "nft_amounts: expected 2 amounts, got 1");
+
+    let tuple = vec![
+        Token::Array(vec![Token::Uint(U256::from(1))]),
+        Token::Array(vec![]),
+        Token::Bytes(vec![]),
+        Token::Bytes(vec![0u8; 20]),
+    ];
+    let err = xerc1155::TransferParams::from_token_tuple(tuple, evm()).unwrap_err();
+    assert_eq!(parse_err_msg(err), "nft_amounts: expected 1 amounts, got 0");
+}
+
+#[test]
+fn crosschain_reply_carries_the_request_identifier() {
+    let reply = |data: Option<Binary>| {
+        SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data,
+        })
+    };
+    let data = to_binary(&CrosschainRequestResponse {
+        request_identifier: 42,
+    })
+    .unwrap();
+    assert_eq!(
+        common::crosschain_request_identifier(reply(Some(data))),
+        Ok(42)
+    );
+    assert_eq!(
+        common::crosschain_request_identifier(reply(None)),
+        Err("missing request identifier".to_string())
+    );
+    assert!(common::crosschain_request_identifier(reply(Some(Binary(vec![1, 2])))).is_err());
+    assert_eq!(
+        common::crosschain_request_identifier(SubMsgResult::Err("out of gas".into())),
+        Err("out of gas".to_string())
+    );
+}
+
+#[test]
+fn inbound_requests_are_checked_and_recorded_once() {
+    const REMOTES: Map<String, String> = Map::new("remotes");
+    const PROCESSED: Map<(String, u64), bool> = Map::new("processed");
+    let mut storage = MockStorage::new();
+    REMOTES
+        .save(&mut storage, "80001".into(), &"0xremote".into())
+        .unwrap();
+
+    assert_eq!(
+        common::check_request_sender(&storage, &REMOTES, "80001", "0xremote"),
+        Ok(())
+    );
+    assert_eq!(
+        common::check_request_sender(&storage, &REMOTES, "80001", "0xother"),
+        Err(common::InboundRequestError::InvalidRequestSender {
+            sender: "0xother".into()
+        })
+    );
+    assert_eq!(
+        common::check_request_sender(&storage, &REMOTES, "43113", "0xremote"),
+        Err(common::InboundRequestError::UnknownSource {
+            chain_id: "43113".into()
+        })
+    );
+
+    assert!(common::record_inbound_request(&mut storage, &PROCESSED, "80001", 1).unwrap());
+    assert!(!common::record_inbound_request(&mut storage, &PROCESSED, "80001", 1).unwrap());
+    // identifiers are per source chain
+    assert!(common::record_inbound_request(&mut storage, &PROCESSED, "43113", 1).unwrap());
+}
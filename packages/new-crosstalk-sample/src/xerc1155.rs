@@ -1,19 +1,88 @@
-use crate::{Deserialize, Serialize};
-use cosmwasm_std::{Binary, CustomMsg, StdResult, Uint128};
+use crate::{abi, common, Deserialize, Serialize};
+use cosmwasm_std::{Binary, CustomMsg, StdError, StdResult, Timestamp, Uint128};
+use cw0::Expiration;
 use router_wasm_bindings::{
     ethabi::{ethereum_types::U256, ParamType, Token},
     types::RequestMetaData,
+    utils::{convert_address_from_bytes_to_string, convert_address_from_string_to_bytes},
     Bytes,
 };
 use schemars::JsonSchema;
+use sha2::{Digest, Sha256};
+
+pub use crate::common::{ChainTypeInfo, TransferStatus};
+
+pub const MINT_VOUCHER_DOMAIN: &[u8] = b"xerc1155:mint_voucher:v1";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub minter: String, // fee payer will be contract itself
     pub xerc1155_codeid: u64,
+    // hex ed25519 key whose vouchers let other addresses mint, `None` leaves minting to `minter`
+    #[serde(default)]
+    pub public_key: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// What the off-chain signer authorizes: `batch` minted to `to`, on one
+/// contract of one chain, until `expires`. Only `nonce`, `expires` and the
+/// signature travel with `ExecuteMsg::Mint` and `ExecuteMsg::BatchMint`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintVoucher {
+    pub contract_address: String,
+    pub chain_id: String,
+    pub to: String,
+    pub batch: Vec<(String, Uint128)>,
+    pub nonce: u64,
+    pub expires: Expiration,
+}
+
+impl MintVoucher {
+    pub fn get_evm_encoding(&self) -> Token {
+        let (token_ids, amounts) = self
+            .batch
+            .iter()
+            .map(|(token_id, amount)| {
+                (
+                    Token::String(token_id.clone()),
+                    Token::Uint(U256::from(amount.u128())),
+                )
+            })
+            .unzip();
+        let [expiry_kind, expiry_value] = common::expiration_tokens(&self.expires);
+        Token::Tuple(vec![
+            Token::String(self.contract_address.clone()),
+            Token::String(self.chain_id.clone()),
+            Token::String(self.to.clone()),
+            Token::Array(token_ids),
+            Token::Array(amounts),
+            Token::Uint(U256::from(self.nonce)),
+            expiry_kind,
+            expiry_value,
+        ])
+    }
+    /// sha256 of `MINT_VOUCHER_DOMAIN || abi.encode(voucher)`, this is what the signer signs
+    pub fn digest(&self) -> [u8; 32] {
+        Sha256::digest(common::mint_voucher_bytes(
+            MINT_VOUCHER_DOMAIN,
+            self.get_evm_encoding(),
+        ))
+        .into()
+    }
+}
+
+/// A voucher signature, for mints sent by someone else than the minter.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedVoucher {
+    // a `(to, nonce)` pair can be used only once
+    pub nonce: u64,
+    // must match the signed voucher, never expires when left out
+    #[serde(default)]
+    pub expires: Expiration,
+    // hex ed25519 signature of `MintVoucher::digest`
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct TransferParams {
     pub nft_ids: Vec<Uint128>,
     pub nft_amounts: Vec<Uint128>,
@@ -22,84 +91,85 @@ pub struct TransferParams {
 }
 
 impl TransferParams {
-    pub fn get_evm_encoding(&self) -> StdResult<Token> {
-        let nft_ids_len = self.nft_ids.clone().len();
-        let nft_amounts_len = self.nft_ids.clone().len();
-        assert!(nft_ids_len == nft_amounts_len); // both length should be equal
-
-        let mut nft_ids = Vec::<Token>::new();
-        for i in 0..nft_ids_len {
-            nft_ids.push(Token::Uint(U256::from(Uint128::u128(
-                &self.nft_ids.clone()[i],
-            ))));
-        }
-        let nft_ids_array = Token::FixedArray(nft_ids);
-
-        let mut nft_amounts = Vec::<Token>::new();
-        for i in 0..nft_amounts_len {
-            nft_amounts.push(Token::Uint(U256::from(Uint128::u128(
-                &self.nft_amounts.clone()[i],
-            ))));
-        }
-        let nft_amounts_array = Token::FixedArray(nft_amounts);
-        let nft_data = Token::Bytes(self.nft_data.clone());
-        let recipient = Token::Bytes(self.recipient.clone().into());
+    // `chain_type` is the code of the destination chain, which decides how the recipient is written
+    pub fn get_evm_encoding(&self, chain_type: u64) -> StdResult<Token> {
+        self.check_lengths()?;
+        let to_uints = |values: &[Uint128]| {
+            Token::Array(
+                values
+                    .iter()
+                    .map(|value| Token::Uint(U256::from(value.u128())))
+                    .collect(),
+            )
+        };
+        let recipient: Bytes =
+            convert_address_from_string_to_bytes(self.recipient.clone(), chain_type)?;
 
         Ok(Token::Tuple(vec![
-            nft_ids_array,
-            nft_amounts_array,
-            nft_data,
-            recipient,
+            to_uints(&self.nft_ids),
+            to_uints(&self.nft_amounts),
+            Token::Bytes(self.nft_data.clone()),
+            Token::Bytes(recipient),
         ]))
     }
     pub fn get_params_types() -> ParamType {
-        return ParamType::Tuple(vec![
+        ParamType::Tuple(vec![
             ParamType::Array(Box::new(ParamType::Uint(256))),
             ParamType::Array(Box::new(ParamType::Uint(256))),
             ParamType::Bytes,
             ParamType::Bytes,
-        ]);
+        ])
     }
-    pub fn from_token_tuple(tuple: Vec<Token>) -> StdResult<Self> {
-        let nfts_ids_token = tuple[0].clone().into_array().unwrap();
-        let mut nft_ids = Vec::<Uint128>::new();
-        for i in 0..nfts_ids_token.len() {
-            nft_ids.push(Uint128::from(
-                nfts_ids_token[i].clone().into_uint().unwrap().as_u128(),
-            ));
-        }
-
-        let nft_amounts_token = tuple[1].clone().into_array().unwrap();
-        let mut nft_amounts = Vec::<Uint128>::new();
-        for i in 0..nft_amounts_token.len() {
-            nft_amounts.push(Uint128::from(
-                nft_amounts_token[i].clone().into_uint().unwrap().as_u128(),
+    // `chain_type` is the code of the chain the recipient lives on
+    pub fn from_token_tuple(tuple: Vec<Token>, chain_type: u64) -> StdResult<Self> {
+        const TARGET: &str = "TransferParams";
+        let [nft_ids, nft_amounts, nft_data, recipient] = abi::fields(TARGET, tuple)?;
+        let to_uint128 = |target: &str, field: &str, token: Token| {
+            abi::uint_u128(target, field, token).map(Uint128::from)
+        };
+        let params = Self {
+            nft_ids: abi::array_of(TARGET, "nft_ids", nft_ids, to_uint128)?,
+            nft_amounts: abi::array_of(TARGET, "nft_amounts", nft_amounts, to_uint128)?,
+            nft_data: abi::bytes(TARGET, "nft_data", nft_data)?,
+            recipient: abi::address(
+                TARGET,
+                "recipient",
+                convert_address_from_bytes_to_string(
+                    &abi::bytes(TARGET, "recipient", recipient)?,
+                    chain_type,
+                ),
+            )?,
+        };
+        params.check_lengths()?;
+        Ok(params)
+    }
+    // every id needs an amount
+    fn check_lengths(&self) -> StdResult<()> {
+        if self.nft_ids.len() != self.nft_amounts.len() {
+            return Err(StdError::parse_err(
+                "TransferParams",
+                format!(
+                    "nft_amounts: expected {} amounts, got {}",
+                    self.nft_ids.len(),
+                    self.nft_amounts.len()
+                ),
             ));
         }
-
-        let nft_data = tuple[2].clone().into_bytes().unwrap();
-        let has_prefix = true;
-        let prefix = if has_prefix { "0x" } else { "" };
-        let recipient = format!(
-            "{}{}",
-            prefix,
-            tuple[3]
-                .clone()
-                .into_bytes()
-                .unwrap()
-                .iter()
-                .map(|b| format!("{:02x}", b))
-                .collect::<String>()
-        );
-        Ok(Self {
-            nft_data,
-            recipient,
-            nft_amounts,
-            nft_ids,
-        })
+        Ok(())
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferRecord {
+    pub request_identifier: u64,
+    pub sender: String,
+    pub batch: Vec<(String, Uint128)>,
+    pub dst_chain_id: String,
+    pub recipient: String,
+    pub created_at: Timestamp,
+    pub status: TransferStatus,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
@@ -110,6 +180,10 @@ pub enum ExecuteMsg {
     SetCw1155ContractAddress {
         address: String,
     },
+    // recipients on chains without a type are written as EVM addresses
+    SetChainTypes {
+        chain_type_info: Vec<ChainTypeInfo>,
+    },
     TransferCrossChain {
         dst_chain_id: String,
         token_ids: Vec<Uint128>,
@@ -122,12 +196,16 @@ pub enum ExecuteMsg {
         to: String,
         batch: Vec<(String, Uint128)>,
         msg: String,
+        #[serde(default)]
+        voucher: Option<SignedVoucher>,
     },
     Mint {
         to: String,
         token_id: Uint128,
         amount: Uint128,
         msg: String,
+        #[serde(default)]
+        voucher: Option<SignedVoucher>,
     },
 }
 
@@ -144,6 +222,9 @@ pub enum QueryMsg {
     GetOwner {},
     GetRemoteContract { chain_id: String },
     GetCw1155Address {},
+    // `null` when no type is set for the chain
+    GetChainType { chain_id: String },
+    GetTransfer { request_identifier: u64 },
 }
 
 impl CustomMsg for QueryMsg {}
@@ -1,17 +1,46 @@
-use crate::{Deserialize, Serialize};
-use cosmwasm_std::{Binary, Uint128};
+use crate::{abi, Deserialize, Serialize};
+use cosmwasm_std::{Binary, StdResult, Uint128};
+use router_wasm_bindings::{
+    ethabi::{ethereum_types::U256, ParamType, Token},
+    types::RequestMetaData,
+    Bytes,
+};
 use schemars::JsonSchema;
 
+pub use crate::common::ChainTypeInfo;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ContractInfo {
     pub chain_id: String,
     pub contract_addr: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct ChainTypeInfo {
-    pub chain_id: String,
-    pub chain_type: u64,
+/// Payload of a cross-chain transfer, `(bytes recipient, uint256 amount)`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TransferParams {
+    // address on the destination chain, already in that chain's encoding
+    pub recipient: Bytes,
+    pub amount: Uint128,
+}
+
+impl TransferParams {
+    pub fn get_evm_encoding(&self) -> Token {
+        Token::Tuple(vec![
+            Token::Bytes(self.recipient.clone()),
+            Token::Uint(U256::from(self.amount.u128())),
+        ])
+    }
+    pub fn get_params_types() -> ParamType {
+        ParamType::Tuple(vec![ParamType::Bytes, ParamType::Uint(256)])
+    }
+    pub fn from_token_tuple(tuple: Vec<Token>) -> StdResult<Self> {
+        const TARGET: &str = "TransferParams";
+        let [recipient, amount] = abi::fields(TARGET, tuple)?;
+        Ok(Self {
+            recipient: abi::bytes(TARGET, "recipient", recipient)?,
+            amount: abi::uint_u128(TARGET, "amount", amount)?.into(),
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -19,6 +48,7 @@ pub struct InstantiateMsg {
     pub cw20_code_id: u64,
     pub token_name: String,
     pub token_symbol: String,
+    pub decimals: u8,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -40,6 +70,7 @@ pub enum ExecuteMsg {
         amount: Uint128,
         recipient: Binary,
         dest_chain_id: String,
+        request_metadata: RequestMetaData,
     },
 }
 
@@ -54,7 +85,15 @@ pub enum QueryMsg {
     FetchOwner {},
     FetchXerc20 {},
     FetchChainId {},
-    FetchChainType { chain_id: String },
-    FetchWhiteListedContract { chain_id: String },
-    AllWhiteListedContract {},
+    FetchChainType {
+        chain_id: String,
+    },
+    FetchWhiteListedContract {
+        chain_id: String,
+    },
+    // ordered by chain id
+    AllWhiteListedContract {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
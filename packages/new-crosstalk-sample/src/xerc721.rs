@@ -1,22 +1,95 @@
-use crate::{Deserialize, Serialize};
-use cosmwasm_std::{CustomMsg, StdResult};
+use crate::{abi, common, Deserialize, Serialize};
+use cosmwasm_std::{CustomMsg, StdError, StdResult, Timestamp};
+use cw0::Expiration;
 use router_wasm_bindings::{
-    ethabi::{ethereum_types::U256, ParamType, Token},
-    types::{ChainType, RequestMetaData},
-    utils::{convert_address_from_string_to_bytes, convert_address_from_bytes_to_string},
+    ethabi::{decode, encode, ethereum_types::U256, ParamType, Token},
+    types::RequestMetaData,
+    utils::{convert_address_from_bytes_to_string, convert_address_from_string_to_bytes},
     Bytes,
 };
 use schemars::JsonSchema;
+use sha2::{Digest, Sha256};
 use std::str;
 
+pub use crate::common::{ChainTypeInfo, TransferStatus};
+
+pub const MINT_VOUCHER_DOMAIN: &[u8] = b"xerc721:mint_voucher:v1";
+// key id of the signer registered from `InstantiateMsg::public_key`
+pub const DEFAULT_SIGNER_KEY_ID: &str = "default";
+
+pub const MAX_BATCH_SIZE: usize = 30;
+
+// locally minted ids are `namespace << TOKEN_ID_SEQUENCE_BITS | sequence`
+pub const TOKEN_ID_SEQUENCE_BITS: u32 = 40;
+pub const MAX_TOKEN_ID_NAMESPACE: u32 = (1 << (64 - TOKEN_ID_SEQUENCE_BITS)) - 1;
+
+/// Id of the `sequence`-th token minted by the deployment owning `namespace`.
+pub fn namespaced_token_id(namespace: u32, sequence: u64) -> u64 {
+    (u64::from(namespace) << TOKEN_ID_SEQUENCE_BITS) | sequence
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub name: String,
     pub symbol: String,
     pub public_key: String,
+    #[serde(default)]
+    pub mint_policy: MintPolicy,
+    // cw721 minter, defaults to the instantiating address
+    #[serde(default)]
+    pub minter: Option<String>,
+    // unique per deployment across the bridged chains, keeps token ids from colliding
+    #[serde(default)]
+    pub token_id_namespace: u32,
+    #[serde(default)]
+    pub bridge_mode: BridgeMode,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// What happens to a token when it leaves or reaches this deployment.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BridgeMode {
+    // burned on the way out, minted on the way in
+    #[default]
+    BurnMint,
+    // held by the contract on the way out and released on the way in, for
+    // the home chain of the collection
+    LockRelease,
+}
+
+/// How many tokens a single address may mint with vouchers.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MintPolicy {
+    OnePerAddress {},
+    PerAddress { limit: u64 },
+    Unlimited {},
+}
+
+impl Default for MintPolicy {
+    fn default() -> Self {
+        MintPolicy::OnePerAddress {}
+    }
+}
+
+impl MintPolicy {
+    /// `None` when there is no cap
+    pub fn per_address_limit(&self) -> Option<u64> {
+        match self {
+            MintPolicy::OnePerAddress {} => Some(1),
+            MintPolicy::PerAddress { limit } => Some(*limit),
+            MintPolicy::Unlimited {} => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RemoteContract {
+    pub chain_id: String,
+    pub remote_address: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct TransferParams {
     pub nft_id: u64,
     pub recipient: String,
@@ -24,37 +97,304 @@ pub struct TransferParams {
 }
 
 impl TransferParams {
-    pub fn get_evm_encoding(&self) -> StdResult<Token> {
-        let token_id = Token::Uint(U256::from(self.nft_id.clone()));
-        let recipient: Bytes = convert_address_from_string_to_bytes(
-            self.recipient.clone(),
-            ChainType::ChainTypeEvm.get_chain_code(), //:: this will not always be evm
-        )?;
+    // `chain_type` is the code of the destination chain, which decides how the recipient is written
+    pub fn get_evm_encoding(&self, chain_type: u64) -> StdResult<Token> {
+        let token_id = Token::Uint(U256::from(self.nft_id));
+        let recipient: Bytes =
+            convert_address_from_string_to_bytes(self.recipient.clone(), chain_type)?;
         let uri = Token::String(self.uri.clone());
 
         Ok(Token::Tuple(vec![token_id, Token::Bytes(recipient), uri]))
     }
     pub fn get_params_types() -> ParamType {
-        return ParamType::Tuple(vec![ParamType::Uint(256), ParamType::Bytes, ParamType::String]);
+        ParamType::Tuple(vec![
+            ParamType::Uint(256),
+            ParamType::Bytes,
+            ParamType::String,
+        ])
     }
-    pub fn from_token_tuple(tuple: Vec<Token>) -> StdResult<Self> {
-        let nft_id = tuple[0].clone().into_uint().unwrap().as_u64();
+    // `chain_type` is the code of the chain the recipient lives on
+    pub fn from_token_tuple(tuple: Vec<Token>, chain_type: u64) -> StdResult<Self> {
+        const TARGET: &str = "TransferParams";
+        let [nft_id, recipient, uri] = abi::fields(TARGET, tuple)?;
+        Ok(Self {
+            nft_id: abi::uint_u64(TARGET, "nft_id", nft_id)?,
+            recipient: abi::address(
+                TARGET,
+                "recipient",
+                convert_address_from_bytes_to_string(
+                    &abi::bytes(TARGET, "recipient", recipient)?,
+                    chain_type,
+                ),
+            )?,
+            uri: abi::string(TARGET, "uri", uri)?,
+        })
+    }
+}
+
+/// Payload of `TransferCrossChainBatch`, `nft_ids[i]` has the URI `uris[i]`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BatchTransferParams {
+    pub nft_ids: Vec<u64>,
+    pub recipient: String,
+    pub uris: Vec<String>,
+}
+
+impl BatchTransferParams {
+    pub fn get_evm_encoding(&self, chain_type: u64) -> StdResult<Token> {
+        let token_ids = self
+            .nft_ids
+            .iter()
+            .map(|nft_id| Token::Uint(U256::from(*nft_id)))
+            .collect();
+        let recipient: Bytes =
+            convert_address_from_string_to_bytes(self.recipient.clone(), chain_type)?;
+        let uris = self
+            .uris
+            .iter()
+            .map(|uri| Token::String(uri.clone()))
+            .collect();
+
+        Ok(Token::Tuple(vec![
+            Token::Array(token_ids),
+            Token::Bytes(recipient),
+            Token::Array(uris),
+        ]))
+    }
+    pub fn get_params_types() -> ParamType {
+        ParamType::Tuple(vec![
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+            ParamType::Bytes,
+            ParamType::Array(Box::new(ParamType::String)),
+        ])
+    }
+    pub fn from_token_tuple(tuple: Vec<Token>, chain_type: u64) -> StdResult<Self> {
+        const TARGET: &str = "BatchTransferParams";
+        let [nft_ids, recipient, uris] = abi::fields(TARGET, tuple)?;
+        Ok(Self {
+            nft_ids: abi::array_of(TARGET, "nft_ids", nft_ids, abi::uint_u64)?,
+            recipient: abi::address(
+                TARGET,
+                "recipient",
+                convert_address_from_bytes_to_string(
+                    &abi::bytes(TARGET, "recipient", recipient)?,
+                    chain_type,
+                ),
+            )?,
+            uris: abi::array_of(TARGET, "uris", uris, abi::string)?,
+        })
+    }
+}
 
-        let recipient = convert_address_from_bytes_to_string(
-            &tuple[1].clone().into_bytes().unwrap(),
-            ChainType::ChainTypeCosmos.get_chain_code(),
-        )
-        .unwrap();
+/// Payload of a `CrossChainMessage::MetadataUpdate`, the new URI of `nft_id`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MetadataUpdateParams {
+    pub nft_id: u64,
+    pub uri: String,
+}
 
-        let uri = tuple[2].clone().into_string().unwrap();
+impl MetadataUpdateParams {
+    pub fn get_evm_encoding(&self) -> Token {
+        Token::Tuple(vec![
+            Token::Uint(U256::from(self.nft_id)),
+            Token::String(self.uri.clone()),
+        ])
+    }
+    pub fn get_params_types() -> ParamType {
+        ParamType::Tuple(vec![ParamType::Uint(256), ParamType::String])
+    }
+    pub fn from_token_tuple(tuple: Vec<Token>) -> StdResult<Self> {
+        const TARGET: &str = "MetadataUpdateParams";
+        let [nft_id, uri] = abi::fields(TARGET, tuple)?;
         Ok(Self {
-            nft_id,
-            recipient,
-            uri,
+            nft_id: abi::uint_u64(TARGET, "nft_id", nft_id)?,
+            uri: abi::string(TARGET, "uri", uri)?,
         })
     }
 }
 
+/// Commands a remote deployment can send to this one.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminCommand {
+    Pause,
+    Unpause,
+}
+
+impl AdminCommand {
+    pub fn code(&self) -> u8 {
+        match self {
+            AdminCommand::Pause => 1,
+            AdminCommand::Unpause => 2,
+        }
+    }
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            1 => Some(AdminCommand::Pause),
+            2 => Some(AdminCommand::Unpause),
+            _ => None,
+        }
+    }
+    pub fn from_token(token: Token) -> StdResult<Self> {
+        const TARGET: &str = "AdminCommand";
+        let code = abi::uint_u8(TARGET, "command", token)?;
+        Self::from_code(code)
+            .ok_or_else(|| StdError::parse_err(TARGET, format!("command: unknown code {}", code)))
+    }
+}
+
+// an envelope is the ABI encoding of `(uint8 version, uint8 message_type, bytes body)`,
+// with an ABI encoded body. Payloads sent before envelopes are bare ABI
+// encodings of a tuple, whose first word is always its offset 32, so
+// versions stay below 32 and the two are never mistaken for each other.
+pub const PAYLOAD_VERSION: u8 = 1;
+// routes on this version get bare payloads, for remotes that predate envelopes
+pub const LEGACY_PAYLOAD_VERSION: u8 = 0;
+pub const MESSAGE_TYPE_TRANSFER: u8 = 1;
+pub const MESSAGE_TYPE_BATCH_TRANSFER: u8 = 2;
+pub const MESSAGE_TYPE_METADATA_UPDATE: u8 = 3;
+pub const MESSAGE_TYPE_ADMIN: u8 = 4;
+
+/// A message exchanged between deployments, sent in a versioned envelope.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum CrossChainMessage {
+    Transfer(TransferParams),
+    BatchTransfer(BatchTransferParams),
+    MetadataUpdate(MetadataUpdateParams),
+    Admin(AdminCommand),
+}
+
+impl CrossChainMessage {
+    pub fn message_type(&self) -> u8 {
+        match self {
+            CrossChainMessage::Transfer(_) => MESSAGE_TYPE_TRANSFER,
+            CrossChainMessage::BatchTransfer(_) => MESSAGE_TYPE_BATCH_TRANSFER,
+            CrossChainMessage::MetadataUpdate(_) => MESSAGE_TYPE_METADATA_UPDATE,
+            CrossChainMessage::Admin(_) => MESSAGE_TYPE_ADMIN,
+        }
+    }
+
+    fn body(&self, chain_type: u64) -> StdResult<Token> {
+        Ok(match self {
+            CrossChainMessage::Transfer(params) => params.get_evm_encoding(chain_type)?,
+            CrossChainMessage::BatchTransfer(params) => params.get_evm_encoding(chain_type)?,
+            CrossChainMessage::MetadataUpdate(params) => params.get_evm_encoding(),
+            CrossChainMessage::Admin(command) => Token::Uint(U256::from(command.code())),
+        })
+    }
+
+    /// The envelope of this message, `chain_type` is the code of the destination chain.
+    pub fn encode_envelope(&self, chain_type: u64) -> StdResult<Bytes> {
+        Ok(encode(&[
+            Token::Uint(U256::from(PAYLOAD_VERSION)),
+            Token::Uint(U256::from(self.message_type())),
+            Token::Bytes(encode(&[self.body(chain_type)?])),
+        ]))
+    }
+
+    /// This message as a route on `payload_version` expects it, a bare ABI
+    /// encoding of the body on `LEGACY_PAYLOAD_VERSION`.
+    pub fn encode_payload(&self, chain_type: u64, payload_version: u8) -> StdResult<Bytes> {
+        match payload_version {
+            PAYLOAD_VERSION => self.encode_envelope(chain_type),
+            LEGACY_PAYLOAD_VERSION => Ok(encode(&[self.body(chain_type)?])),
+            _ => Err(StdError::generic_err(format!(
+                "unsupported payload version {}",
+                payload_version
+            ))),
+        }
+    }
+}
+
+/// Splits an envelope into its version, message type and body, `None` for
+/// a payload sent before envelopes.
+pub fn split_envelope(payload: &[u8]) -> StdResult<Option<(u8, u8, Bytes)>> {
+    const TARGET: &str = "envelope";
+    match payload.get(..32) {
+        Some(word) if U256::from_big_endian(word) < U256::from(32) => {}
+        _ => return Ok(None),
+    }
+    let tokens = decode(
+        &[ParamType::Uint(8), ParamType::Uint(8), ParamType::Bytes],
+        payload,
+    )
+    .map_err(|err| StdError::parse_err(TARGET, format!("{:?}", err)))?;
+    if encode(&tokens) != payload {
+        return Err(StdError::parse_err(TARGET, "not a canonical encoding"));
+    }
+    let [version, message_type, body] = abi::fields(TARGET, tokens)?;
+    Ok(Some((
+        abi::uint_u8(TARGET, "version", version)?,
+        abi::uint_u8(TARGET, "message_type", message_type)?,
+        abi::bytes(TARGET, "body", body)?,
+    )))
+}
+
+/// What the off-chain signer authorizes: one `token_uri` for one minter,
+/// on one contract of one chain. The contract rebuilds it from the
+/// transaction context, so only `token_uri`, `nonce` and `expires` travel
+/// in `ExecuteMsg::MintToken`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintVoucher {
+    pub contract_address: String,
+    pub chain_id: String,
+    pub minter: String,
+    pub token_uri: String,
+    pub nonce: u64,
+    pub expires: Expiration,
+}
+
+impl MintVoucher {
+    pub fn get_evm_encoding(&self) -> Token {
+        let [expiry_kind, expiry_value] = common::expiration_tokens(&self.expires);
+        Token::Tuple(vec![
+            Token::String(self.contract_address.clone()),
+            Token::String(self.chain_id.clone()),
+            Token::String(self.minter.clone()),
+            Token::String(self.token_uri.clone()),
+            Token::Uint(U256::from(self.nonce)),
+            expiry_kind,
+            expiry_value,
+        ])
+    }
+    /// `MINT_VOUCHER_DOMAIN || abi.encode(voucher)`
+    pub fn canonical_bytes(&self) -> Bytes {
+        common::mint_voucher_bytes(MINT_VOUCHER_DOMAIN, self.get_evm_encoding())
+    }
+    /// sha256 of the canonical bytes, this is what the signer signs
+    pub fn digest(&self) -> [u8; 32] {
+        Sha256::digest(self.canonical_bytes()).into()
+    }
+}
+
+/// How a signer key signs voucher digests.
+/// `Secp256k1` keys sign the EIP-191 personal-sign hash of the digest, so an
+/// Ethereum wallet can use `signMessage(digest)`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureScheme {
+    #[default]
+    Ed25519,
+    Secp256k1,
+}
+
+/// Permissions the owner can hand out, `admin` implies all the others.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    SignerManager,
+    BridgeManager,
+    Pauser,
+    Minter,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoucherSignature {
+    pub key_id: String,
+    pub signature: String,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
@@ -62,31 +402,293 @@ pub enum ExecuteMsg {
         chain_id: String,
         remote_address: String,
     },
+    EnrollRemoteContracts {
+        contracts: Vec<RemoteContract>,
+    },
+    UnenrollRemoteContract {
+        chain_id: String,
+    },
+    // stops transfers to and from an enrolled chain, without forgetting its remote contract
+    DisableChain {
+        chain_id: String,
+    },
+    EnableChain {
+        chain_id: String,
+    },
+    // recipients on chains without a type are written as EVM addresses
+    SetChainTypes {
+        chain_type_info: Vec<ChainTypeInfo>,
+    },
+    // `PAYLOAD_VERSION` sends envelopes to the chain, `LEGACY_PAYLOAD_VERSION` (the default) bare payloads
+    SetPayloadVersion {
+        chain_id: String,
+        version: u8,
+    },
+    // owner only, admin commands such as a remote pause are taken only from allowed chains
+    SetAdminSource {
+        chain_id: String,
+        allowed: bool,
+    },
     TransferCrossChain {
         dst_chain_id: String,
         token_id: u64,
         recipient: String,
         request_metadata: RequestMetaData,
     },
+    // up to `MAX_BATCH_SIZE` tokens in a single Router request
+    TransferCrossChainBatch {
+        dst_chain_id: String,
+        token_ids: Vec<u64>,
+        recipient: String,
+        request_metadata: RequestMetaData,
+    },
     MintToken {
         token_uri: String,
-        signature: String,
+        nonce: u64,
+        #[serde(default)]
+        expires: Expiration,
+        signatures: Vec<VoucherSignature>,
+    },
+    AddSigner {
+        key_id: String,
+        public_key: String,
+        #[serde(default)]
+        scheme: SignatureScheme,
+    },
+    RevokeSigner {
+        key_id: String,
+    },
+    // revokes `key_id` and activates `new_key_id` in one step
+    RotateSigner {
+        key_id: String,
+        new_key_id: String,
+        public_key: String,
+        #[serde(default)]
+        scheme: SignatureScheme,
+    },
+    // number of distinct active signers a voucher needs
+    SetSignerThreshold {
+        threshold: u32,
+    },
+    // unset fields keep their current value
+    UpdateCollectionInfo {
+        name: Option<String>,
+        symbol: Option<String>,
+    },
+    UpdateMinter {
+        minter: String,
+    },
+    // ownership moves only once `new_owner` accepts it
+    ProposeNewOwner {
+        new_owner: String,
+        #[serde(default)]
+        expires: Expiration,
+    },
+    AcceptOwnership {},
+    CancelOwnershipTransfer {},
+    // leaves the contract without an owner, this cannot be undone
+    RenounceOwnership {},
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    RevokeRole {
+        role: Role,
+        address: String,
     },
+    // stops voucher mints, admin mints and outbound transfers
+    Pause {},
+    Unpause {},
 }
 
 impl CustomMsg for ExecuteMsg {}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    // collection info and cw721 minter, required when upgrading a 1.0.0 deployment,
+    // which never saved them; the minter defaults to the owner
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub minter: Option<String>,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     // fetch contract version
     GetContractVersion {},
+    // `null` once ownership has been renounced
     GetOwner {},
-    GetRemoteContract { chain_id: String },
-    IsAlreadyMinted { owner: String }
+    GetPendingOwner {},
+    GetRemoteContract {
+        chain_id: String,
+    },
+    // ordered by chain id
+    ListRemoteContracts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    IsChainEnabled {
+        chain_id: String,
+    },
+    // `null` when no type is set for the chain
+    GetChainType {
+        chain_id: String,
+    },
+    GetPayloadVersion {
+        chain_id: String,
+    },
+    IsAdminSource {
+        chain_id: String,
+    },
+    IsAlreadyMinted {
+        owner: String,
+    },
+    MintAllowance {
+        owner: String,
+    },
+    ListSigners {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    GetSignerThreshold {},
+    RolesOf {
+        address: String,
+    },
+    IsPaused {},
+    GetTransfer {
+        request_identifier: u64,
+    },
+    // newest request identifiers are the largest, pages go from oldest to newest
+    TransfersBySender {
+        sender: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    TransfersByToken {
+        token_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetTokenIdNamespace {},
+    GetBridgeMode {},
+    // tokens held by the contract while they live on another chain
+    ListEscrowed {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // dry run of `TransferCrossChainBatch` sent by `sender`, a single transfer is a batch of one
+    CheckTransfer {
+        sender: String,
+        dst_chain_id: String,
+        token_ids: Vec<u64>,
+        recipient: String,
+        request_metadata: RequestMetaData,
+    },
+    // whether an inbound request has already been executed
+    IsRequestProcessed {
+        src_chain_id: String,
+        request_identifier: u64,
+    },
 }
 
 impl CustomMsg for QueryMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintAllowanceResponse {
+    pub minted: u64,
+    // `None` when the mint policy is unlimited
+    pub remaining: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwnerResponse {
+    // `None` when no transfer is in progress
+    pub pending_owner: Option<String>,
+    pub expires: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub roles: Vec<Role>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RemoteContractInfo {
+    pub chain_id: String,
+    pub remote_address: String,
+    // `None` when no type is set, recipients are then written as EVM addresses
+    pub chain_type: Option<u64>,
+    pub enabled: bool,
+    pub payload_version: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RemoteContractsResponse {
+    pub contracts: Vec<RemoteContractInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CheckTransferResponse {
+    pub ok: bool,
+    // why the transfer would fail
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignerInfo {
+    pub key_id: String,
+    pub public_key: String,
+    pub scheme: SignatureScheme,
+    pub active: bool,
+    pub added_at: Timestamp,
+    pub added_at_height: u64,
+    pub revoked_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignersResponse {
+    pub signers: Vec<SignerInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferredToken {
+    pub token_id: u64,
+    // token owner at the time of the transfer, refunds go to this address
+    pub owner: String,
+    pub token_uri: Option<String>,
+}
+
+/// An outbound cross-chain transfer of one or more tokens, as recorded when
+/// they left this deployment.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferRecord {
+    pub request_identifier: u64,
+    pub tokens: Vec<TransferredToken>,
+    pub sender: String,
+    pub dst_chain_id: String,
+    pub recipient: String,
+    pub created_at: Timestamp,
+    pub created_at_height: u64,
+    pub status: TransferStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransfersResponse {
+    pub transfers: Vec<TransferRecord>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EscrowedToken {
+    pub token_id: u64,
+    // owner when the token was locked
+    pub owner: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EscrowedTokensResponse {
+    pub tokens: Vec<EscrowedToken>,
+}